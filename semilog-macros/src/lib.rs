@@ -1,44 +1,70 @@
 use proc_macro2::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam, Index};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DataEnum, DataUnion, DeriveInput, Field,
+    Fields, GenericParam, Ident, Index, Lit, Member, Meta, MetaNameValue, NestedMeta, WhereClause,
+};
 
-#[proc_macro_derive(Semilattice)]
+#[proc_macro_derive(Semilattice, attributes(semilattice))]
 pub fn derive_semilattice(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
 
-    let semilattice_impl = {
-        let mut generics = input.generics.clone();
+    // Don't emit the impls, which would only bury the error.
+    if let Data::Union(ref data) = input.data {
+        return union_unsupported(data).into();
+    }
+
+    let container = match container(&input.attrs) {
+        Ok(container) => container,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
+    let mut generics = input.generics;
+    if let Some(bound) = container.bound {
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(bound.predicates);
+    } else {
         for param in &mut generics.params {
             if let GenericParam::Type(ref mut type_param) = *param {
                 type_param.bounds.push(parse_quote!(semilog::Semilattice));
             }
         }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let semilattice_impl = {
         let join = semilattice_join(&input.data);
+        let join_assign = semilattice_join_assign(&input.data);
+        let bottom = container.identity.then(|| {
+            let bottom = semilattice_bottom(&input.data);
+            quote! {
+                fn bottom() -> Self {
+                    #bottom
+                }
+            }
+        });
 
         quote!(
             impl #impl_generics semilog::Semilattice for #name #ty_generics #where_clause {
                 fn join(self, other: Self) -> Self {
                     #join
                 }
+
+                fn join_assign(&mut self, other: Self) {
+                    #join_assign
+                }
+
+                #bottom
             }
         )
     };
 
     let partial_cmp = {
-        let mut generics = input.generics;
-
-        for param in &mut generics.params {
-            if let GenericParam::Type(ref mut type_param) = *param {
-                type_param.bounds.push(parse_quote!(semilog::Semilattice));
-            }
-        }
-
-        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         let partial_cmp = partial_ord_cmp(&input.data);
 
         quote!(
@@ -58,14 +84,76 @@ pub fn derive_semilattice(input: proc_macro::TokenStream) -> proc_macro::TokenSt
     .into()
 }
 
+/// Container attributes:
+///
+/// - `#[semilattice(bound = "T: Ord")]` replaces the `semilog::Semilattice`
+///   bound otherwise added to every type parameter.
+/// - `#[semilattice(identity)]` constructs `Semilattice::bottom` from the
+///   bottom of each field, rather than from `Default`.
+#[derive(Default)]
+struct Container {
+    bound: Option<WhereClause>,
+    identity: bool,
+}
+
+fn container(attrs: &[Attribute]) -> syn::Result<Container> {
+    let mut container = Container::default();
+
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("semilattice"))
+    {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected #[semilattice(...)]",
+                ))
+            }
+        };
+
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    ref path,
+                    lit: Lit::Str(ref lit),
+                    ..
+                })) if path.is_ident("bound") => {
+                    let clause = syn::parse_str(&format!("where {}", lit.value()))
+                        .map_err(|err| syn::Error::new_spanned(lit, err))?;
+                    container.bound = Some(clause);
+                }
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("identity") => {
+                    container.identity = true;
+                }
+                nested => {
+                    return Err(syn::Error::new_spanned(
+                        nested,
+                        "unknown semilattice container attribute",
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(container)
+}
+
 fn semilattice_join(data: &Data) -> TokenStream {
     match *data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
                 let fields = fields.named.iter().map(|f| {
                     let name = &f.ident;
-                    quote_spanned! { f.span() =>
-                        #name: semilog::Semilattice::join(self.#name, other.#name),
+                    if skipped(f) {
+                        quote_spanned! { f.span() =>
+                            #name: self.#name,
+                        }
+                    } else {
+                        quote_spanned! { f.span() =>
+                            #name: semilog::Semilattice::join(self.#name, other.#name),
+                        }
                     }
                 });
                 quote! {
@@ -77,8 +165,14 @@ fn semilattice_join(data: &Data) -> TokenStream {
             Fields::Unnamed(ref fields) => {
                 let fields = fields.unnamed.iter().enumerate().map(|(i, f)| {
                     let index = Index::from(i);
-                    quote_spanned! { f.span() =>
-                        semilog::Semilattice::join(self.#index, other.#index),
+                    if skipped(f) {
+                        quote_spanned! { f.span() =>
+                            self.#index,
+                        }
+                    } else {
+                        quote_spanned! { f.span() =>
+                            semilog::Semilattice::join(self.#index, other.#index),
+                        }
                     }
                 });
                 quote! {
@@ -89,7 +183,69 @@ fn semilattice_join(data: &Data) -> TokenStream {
                 quote!(Self)
             }
         },
-        Data::Enum(_) | Data::Union(_) => unimplemented!(),
+        Data::Enum(ref data) => enum_join(data),
+        Data::Union(ref data) => union_unsupported(data),
+    }
+}
+
+/// Join each field in place, rather than moving the whole structure.
+fn semilattice_join_assign(data: &Data) -> TokenStream {
+    match *data {
+        Data::Struct(ref data) => {
+            let fields = data.fields.iter().zip(members(&data.fields));
+            let fields: Vec<_> = fields
+                .filter(|(f, _)| !skipped(f))
+                .map(|(f, member)| {
+                    quote_spanned! { f.span() =>
+                        semilog::Semilattice::join_assign(&mut self.#member, other.#member);
+                    }
+                })
+                .collect();
+
+            if fields.is_empty() {
+                quote!(let _ = other;)
+            } else {
+                quote!(#(#fields)*)
+            }
+        }
+        Data::Enum(ref data) => enum_join_assign(data),
+        Data::Union(ref data) => union_unsupported(data),
+    }
+}
+
+/// The first variant of an enum is its least element.
+fn semilattice_bottom(data: &Data) -> TokenStream {
+    let construct = |path: TokenStream, fields: &Fields| {
+        let bottoms = fields.iter().map(|f| {
+            if skipped(f) {
+                quote_spanned!(f.span() => core::default::Default::default())
+            } else {
+                quote_spanned!(f.span() => semilog::Semilattice::bottom())
+            }
+        });
+        let members = members(fields);
+
+        match *fields {
+            Fields::Named(_) => quote!(#path { #(#members: #bottoms,)* }),
+            Fields::Unnamed(_) => quote!(#path(#(#bottoms,)*)),
+            Fields::Unit => path,
+        }
+    };
+
+    match *data {
+        Data::Struct(ref data) => construct(quote!(Self), &data.fields),
+        Data::Enum(ref data) => match data.variants.first() {
+            Some(v) => {
+                let variant = &v.ident;
+                construct(quote!(Self::#variant), &v.fields)
+            }
+            None => syn::Error::new_spanned(
+                data.enum_token,
+                "an enum without variants has no bottom element",
+            )
+            .to_compile_error(),
+        },
+        Data::Union(ref data) => union_unsupported(data),
     }
 }
 
@@ -97,7 +253,7 @@ fn partial_ord_cmp(data: &Data) -> TokenStream {
     match *data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
-                let orders = fields.named.iter().map(|f| {
+                let orders = fields.named.iter().filter(|f| !skipped(f)).map(|f| {
                     let name = &f.ident;
                     quote_spanned! { f.span() =>
                         PartialOrd::partial_cmp(&self.#name, &other.#name),
@@ -108,7 +264,8 @@ fn partial_ord_cmp(data: &Data) -> TokenStream {
                 }
             }
             Fields::Unnamed(ref fields) => {
-                let orders = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                let orders = fields.unnamed.iter().enumerate();
+                let orders = orders.filter(|(_, f)| !skipped(f)).map(|(i, f)| {
                     let index = Index::from(i);
                     quote_spanned! { f.span() =>
                         PartialOrd::partial_cmp(&self.#index, &other.#index),
@@ -122,6 +279,190 @@ fn partial_ord_cmp(data: &Data) -> TokenStream {
                 quote!(core::option::Option::Some(core::cmp::Ordering::Equal))
             }
         },
-        Data::Enum(_) | Data::Union(_) => unimplemented!(),
+        Data::Enum(ref data) => enum_partial_cmp(data),
+        Data::Union(ref data) => union_unsupported(data),
+    }
+}
+
+fn union_unsupported(data: &DataUnion) -> TokenStream {
+    syn::Error::new_spanned(data.union_token, "Semilattice cannot be derived for unions")
+        .to_compile_error()
+}
+
+/// Fields marked `#[semilattice(skip)]` keep the value from `self` and do not
+/// participate in the partial order.
+fn skipped(field: &Field) -> bool {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("semilattice"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| matches!(nested, NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("skip")))
+}
+
+/// The members of a variant's fields, so that named and tuple variants can
+/// both be matched with braces, e.g. `Self::V { 0: x }`.
+fn members(fields: &Fields) -> Vec<Member> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match f.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect()
+}
+
+fn bindings(prefix: &str, members: &[Member]) -> Vec<Ident> {
+    members
+        .iter()
+        .enumerate()
+        .map(|(i, m)| Ident::new(&format!("{}_{}", prefix, i), m.span()))
+        .collect()
+}
+
+/// Variants are ordered by declaration. A closure is emitted rather than a
+/// function so that `Self` remains in scope.
+fn variant_index(data: &DataEnum) -> TokenStream {
+    let arms = data.variants.iter().enumerate().map(|(i, v)| {
+        let variant = &v.ident;
+        quote!(Self::#variant { .. } => #i,)
+    });
+
+    quote! {
+        let variant_index = |x: &Self| -> usize {
+            match *x {
+                #(#arms)*
+            }
+        };
+    }
+}
+
+/// Matching variants join field-wise, otherwise the later variant wins.
+fn enum_join(data: &DataEnum) -> TokenStream {
+    let variant_index = variant_index(data);
+
+    let arms = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        let members = members(&v.fields);
+        let lhs = bindings("__self", &members);
+        let rhs = bindings("__other", &members);
+        let joined = v
+            .fields
+            .iter()
+            .zip(lhs.iter().zip(&rhs))
+            .map(|(f, (l, r))| {
+                if skipped(f) {
+                    quote!(#l)
+                } else {
+                    quote!(semilog::Semilattice::join(#l, #r))
+                }
+            });
+        let joined = match v.fields {
+            Fields::Named(_) => quote!(Self::#variant { #(#members: #joined,)* }),
+            Fields::Unnamed(_) => quote!(Self::#variant(#(#joined,)*)),
+            Fields::Unit => quote!(Self::#variant),
+        };
+
+        quote_spanned! { v.span() =>
+            (
+                Self::#variant { #(#members: #lhs,)* },
+                Self::#variant { #(#members: #rhs,)* },
+            ) => #joined,
+        }
+    });
+
+    quote! {
+        #variant_index
+
+        match (self, other) {
+            #(#arms)*
+            #[allow(unreachable_patterns)]
+            (a, b) => {
+                if variant_index(&a) < variant_index(&b) {
+                    b
+                } else {
+                    a
+                }
+            }
+        }
+    }
+}
+
+fn enum_join_assign(data: &DataEnum) -> TokenStream {
+    let variant_index = variant_index(data);
+
+    let arms = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        let members = members(&v.fields);
+        let lhs = bindings("__self", &members);
+        let rhs = bindings("__other", &members);
+        let fields = v.fields.iter().zip(lhs.iter().zip(&rhs));
+        let fields = fields
+            .filter(|(f, _)| !skipped(f))
+            .map(|(_, (l, r))| quote!(semilog::Semilattice::join_assign(#l, #r);));
+
+        quote_spanned! { v.span() =>
+            (
+                Self::#variant { #(#members: #lhs,)* },
+                Self::#variant { #(#members: #rhs,)* },
+            ) => {
+                #(#fields)*
+            }
+        }
+    });
+
+    quote! {
+        #variant_index
+
+        match (self, other) {
+            #(#arms)*
+            #[allow(unreachable_patterns)]
+            (a, b) => {
+                if variant_index(a) < variant_index(&b) {
+                    *a = b;
+                }
+            }
+        }
+    }
+}
+
+/// Matching variants compare field-wise, and are incomparable if their fields
+/// diverge. Otherwise the variants are ordered by declaration.
+fn enum_partial_cmp(data: &DataEnum) -> TokenStream {
+    let variant_index = variant_index(data);
+
+    let arms = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        let members = members(&v.fields);
+        let lhs = bindings("__self", &members);
+        let rhs = bindings("__other", &members);
+        let orders = v.fields.iter().zip(lhs.iter().zip(&rhs));
+        let orders = orders
+            .filter(|(f, _)| !skipped(f))
+            .map(|(_, (l, r))| quote!(PartialOrd::partial_cmp(#l, #r)));
+
+        quote_spanned! { v.span() =>
+            (
+                Self::#variant { #(#members: #lhs,)* },
+                Self::#variant { #(#members: #rhs,)* },
+            ) => semilog::partial_ord_helper([#(#orders,)*]),
+        }
+    });
+
+    quote! {
+        #variant_index
+
+        match (self, other) {
+            #(#arms)*
+            #[allow(unreachable_patterns)]
+            (a, b) => core::option::Option::Some(
+                core::cmp::Ord::cmp(&variant_index(a), &variant_index(b))
+            ),
+        }
     }
 }
@@ -0,0 +1,117 @@
+use alloc::borrow::ToOwned;
+use core::borrow::Borrow;
+
+use crate::{self as semilog, MapLattice, Max, Semilattice};
+
+/// A vector clock, being the greatest sequence number of each actor which has
+/// been observed. A clock dominates another if it has observed everything
+/// which the other has, and the two are concurrent if neither does.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "A: Ord")]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[cfg_attr(
+    feature = "minicbor",
+    derive(minicbor::Encode, minicbor::Decode),
+    cbor(transparent)
+)]
+pub struct VectorClock<A> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub inner: MapLattice<A, Max<u64>>,
+}
+
+impl<A> Default for VectorClock<A> {
+    fn default() -> Self {
+        Self {
+            inner: MapLattice::default(),
+        }
+    }
+}
+
+impl<A> VectorClock<A>
+where
+    A: Ord,
+{
+    /// Record that `seq` of `actor`, and so every earlier sequence number,
+    /// has been observed.
+    pub fn observe<Q>(&mut self, actor: &Q, seq: u64)
+    where
+        A: Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = A>,
+    {
+        // Zero is implied for every actor, so is not stored.
+        if seq > self.get(actor) {
+            *self.inner.entry_mut(actor) = Max(seq);
+        }
+    }
+
+    /// The greatest sequence number of `actor` which has been observed, or
+    /// zero if none has.
+    pub fn get<Q>(&self, actor: &Q) -> u64
+    where
+        A: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.inner.get(actor).map_or(0, |seq| seq.0)
+    }
+
+    /// Whether this clock has observed everything which `other` has.
+    pub fn dominates(&self, other: &Self) -> bool {
+        semilog::dominates(self, other)
+    }
+
+    /// Whether each clock has observed something which the other has not.
+    pub fn is_concurrent(&self, other: &Self) -> bool {
+        semilog::is_concurrent(self, other)
+    }
+}
+
+#[test]
+fn check_laws() {
+    use alloc::string::String;
+
+    use crate::partially_verify_semilattice_laws;
+
+    let mut a = VectorClock::<String>::default();
+    a.observe("alice", 2);
+    let mut b = a.clone();
+    b.observe("bob", 1);
+    a.observe("alice", 3);
+
+    partially_verify_semilattice_laws([VectorClock::default(), a.clone(), b.clone(), a.join(b)]);
+}
+
+#[test]
+fn check_causality() {
+    use alloc::string::String;
+
+    let mut a = VectorClock::<String>::default();
+    a.observe("alice", 2);
+    a.observe("alice", 1);
+    assert_eq!(a.get("alice"), 2);
+    assert_eq!(a.get("bob"), 0);
+
+    // Observing nothing is the same as not observing at all.
+    let mut b = a.clone();
+    b.observe("bob", 0);
+    assert_eq!(a, b);
+    assert!(a.dominates(&b) && b.dominates(&a));
+
+    // A clock which has observed more dominates.
+    b.observe("bob", 1);
+    assert!(b.dominates(&a) && !a.dominates(&b));
+    assert!(!a.is_concurrent(&b));
+
+    // Clocks which have each observed something new are concurrent, until
+    // joined.
+    a.observe("alice", 3);
+    assert!(a.is_concurrent(&b) && b.is_concurrent(&a));
+    assert!(!a.dominates(&b) && !b.dominates(&a));
+
+    let ab = a.clone().join(b.clone());
+    assert!(ab.dominates(&a) && ab.dominates(&b));
+    assert_eq!((ab.get("alice"), ab.get("bob")), (3, 1));
+}
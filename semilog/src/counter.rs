@@ -0,0 +1,139 @@
+use alloc::borrow::ToOwned;
+use core::borrow::Borrow;
+
+use crate::{self as semilog, MapLattice, Max, Semilattice};
+
+/// A grow-only counter. Each actor increments only its own entry, and the
+/// value of the counter is the sum over all actors.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "A: Ord")]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[cfg_attr(
+    feature = "minicbor",
+    derive(minicbor::Encode, minicbor::Decode),
+    cbor(transparent)
+)]
+pub struct GCounter<A> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub inner: MapLattice<A, Max<u64>>,
+}
+
+impl<A> Default for GCounter<A> {
+    fn default() -> Self {
+        Self {
+            inner: MapLattice::default(),
+        }
+    }
+}
+
+impl<A> GCounter<A>
+where
+    A: Ord,
+{
+    pub fn inc<Q>(&mut self, actor: &Q, n: u64)
+    where
+        A: Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = A>,
+    {
+        let count = self.inner.entry_mut(actor);
+        count.0 = count.0.saturating_add(n);
+    }
+
+    pub fn value(&self) -> u64 {
+        self.inner
+            .values()
+            .fold(0, |sum, count| sum.saturating_add(count.0))
+    }
+}
+
+/// A counter which may be incremented and decremented, as a pair of grow-only
+/// counters.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "A: Ord")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub struct PNCounter<A> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub positive: GCounter<A>,
+    #[cfg_attr(feature = "minicbor", n(1))]
+    pub negative: GCounter<A>,
+}
+
+impl<A> Default for PNCounter<A> {
+    fn default() -> Self {
+        Self {
+            positive: GCounter::default(),
+            negative: GCounter::default(),
+        }
+    }
+}
+
+impl<A> PNCounter<A>
+where
+    A: Ord,
+{
+    pub fn inc<Q>(&mut self, actor: &Q, n: u64)
+    where
+        A: Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = A>,
+    {
+        self.positive.inc(actor, n);
+    }
+
+    pub fn dec<Q>(&mut self, actor: &Q, n: u64)
+    where
+        A: Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = A>,
+    {
+        self.negative.inc(actor, n);
+    }
+
+    /// The difference between the increments and the decrements, saturating
+    /// at the bounds of `i64`.
+    pub fn value(&self) -> i64 {
+        let value = i128::from(self.positive.value()) - i128::from(self.negative.value());
+        value.clamp(i64::MIN.into(), i64::MAX.into()) as i64
+    }
+}
+
+#[test]
+fn check_laws() {
+    use alloc::string::String;
+
+    use crate::partially_verify_semilattice_laws;
+
+    let mut a = PNCounter::<String>::default();
+    a.inc("alice", 3);
+
+    // Alice and Bob count concurrently.
+    let mut b = a.clone();
+    b.inc("bob", 2);
+    a.inc("alice", 1);
+    a.dec("alice", 7);
+
+    let c = a.clone().join(b.clone());
+    assert_eq!(c.positive.value(), 6);
+    assert_eq!(c.value(), -1);
+
+    partially_verify_semilattice_laws([a, b, c]);
+}
+
+#[test]
+fn check_saturation() {
+    use alloc::string::String;
+
+    let mut counter = PNCounter::<String>::default();
+    counter.inc("alice", u64::MAX);
+    assert_eq!(counter.value(), i64::MAX);
+
+    counter.dec("bob", u64::MAX - 1);
+    assert_eq!(counter.value(), 1);
+
+    let mut counter = PNCounter::<String>::default();
+    counter.dec("alice", u64::MAX);
+    assert_eq!(counter.value(), i64::MIN);
+}
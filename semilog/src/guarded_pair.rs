@@ -1,6 +1,6 @@
 use core::cmp::{Ordering, PartialOrd};
 
-use crate::Semilattice;
+use crate::{Max, Semilattice};
 
 /// A pair of semilattices, where the former acts as to version the latter.
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
@@ -13,6 +13,36 @@ pub struct GuardedPair<G, V> {
     pub value: V,
 }
 
+impl<G, V> GuardedPair<G, V> {
+    pub fn new(guard: G, value: V) -> Self {
+        Self { guard, value }
+    }
+
+    pub fn guard(&self) -> &G {
+        &self.guard
+    }
+
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+}
+
+impl<V: Semilattice> GuardedPair<Max<u64>, V> {
+    /// Replace the value with one which supersedes it, and any concurrent
+    /// value with the same guard, by incrementing the guard. Once the guard
+    /// is saturated, the value is joined instead, since replacing it without
+    /// increasing the guard could lose information which replicas keep.
+    pub fn bump(&mut self, value: V) {
+        match self.guard.0.checked_add(1) {
+            Some(guard) => {
+                self.guard.0 = guard;
+                self.value = value;
+            }
+            None => self.value.join_assign(value),
+        }
+    }
+}
+
 impl<G, V> PartialOrd for GuardedPair<G, V>
 where
     G: Semilattice,
@@ -49,7 +79,7 @@ where
 
 #[test]
 fn check_laws() {
-    use crate::{partially_verify_semilattice_laws, Max, Min};
+    use crate::{partially_verify_semilattice_laws, Min};
 
     partially_verify_semilattice_laws([
         GuardedPair {
@@ -70,3 +100,33 @@ fn check_laws() {
         },
     ]);
 }
+
+#[test]
+fn check_bump() {
+    let base = GuardedPair::new(Max(0), Max(5));
+
+    let mut a = base;
+    a.bump(Max(1));
+    assert_eq!((a.guard(), a.value()), (&Max(1), &Max(1)));
+
+    // A greater guard replaces the value, whereas an equal guard joins them.
+    assert_eq!(base.join(a), a);
+
+    let mut b = base;
+    b.bump(Max(2));
+    assert_eq!(a.join(b), GuardedPair::new(Max(1), Max(2)));
+}
+
+#[test]
+fn check_saturated_bump() {
+    let base = GuardedPair::new(Max(u64::MAX), Max(5));
+
+    let mut a = base;
+    a.bump(Max(1));
+    assert_eq!(a, base);
+
+    let mut b = base;
+    b.bump(Max(7));
+    assert_eq!(b, GuardedPair::new(Max(u64::MAX), Max(7)));
+    assert_eq!(base.join(b), b);
+}
@@ -0,0 +1,91 @@
+use core::cmp::{Ordering, PartialEq, PartialOrd};
+
+use crate::Semilattice;
+
+/// A value which is written once and must not diverge between replicas, e.g.
+/// the author of a message. The contained data is arbitrary, not a
+/// semilattice. Joining different values does not panic, but collapses to the
+/// conflicted state, which is greater than every value.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub enum Immutable<T> {
+    #[default]
+    #[cfg_attr(feature = "minicbor", n(0))]
+    Unset,
+    #[cfg_attr(feature = "minicbor", n(1))]
+    Value(#[cfg_attr(feature = "minicbor", n(0))] T),
+    #[cfg_attr(feature = "minicbor", n(2))]
+    Conflicted,
+}
+
+impl<T> Immutable<T> {
+    /// The value, unless it is unset or conflicted.
+    pub fn get(&self) -> Option<&T> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        matches!(self, Self::Conflicted)
+    }
+}
+
+impl<T> PartialOrd for Immutable<T>
+where
+    T: Eq,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        use Immutable::{Conflicted, Unset};
+
+        match (self, other) {
+            (a, b) if a == b => Some(Ordering::Equal),
+            (_, Unset) | (Conflicted, _) => Some(Ordering::Greater),
+            (Unset, _) | (_, Conflicted) => Some(Ordering::Less),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Semilattice for Immutable<T>
+where
+    T: Eq,
+{
+    fn join(self, other: Self) -> Self {
+        use Immutable::{Conflicted, Unset};
+
+        match (self, other) {
+            (a, Unset) => a,
+            (Unset, b) => b,
+            (a, b) if a == b => a,
+            _ => Conflicted,
+        }
+    }
+}
+
+#[test]
+fn check_laws() {
+    use crate::partially_verify_semilattice_laws;
+
+    use Immutable::{Conflicted, Unset, Value};
+
+    partially_verify_semilattice_laws([Unset, Value("alice"), Value("bob"), Conflicted]);
+}
+
+#[test]
+fn check_joins() {
+    use Immutable::{Conflicted, Unset, Value};
+
+    assert_eq!(Unset.join(Value(1)), Value(1));
+    assert_eq!(Value(1).join(Value(1)), Value(1));
+    assert_eq!(Value(1).get(), Some(&1));
+
+    // Divergent values conflict, as does anything joined with a conflict.
+    assert_eq!(Value(1).join(Value(2)), Conflicted);
+    assert_eq!(Value(1).partial_cmp(&Value(2)), None);
+    assert_eq!(Conflicted.join(Value(1)), Conflicted);
+    assert!(Value(1).join(Value(2)).is_conflicted());
+    assert_eq!(Conflicted::<u8>.get(), None);
+}
@@ -1,6 +1,5 @@
 #![no_std]
 #![forbid(unsafe_code)]
-#![cfg_attr(feature = "alloc", feature(slice_partition_dedup))]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -11,29 +10,51 @@ pub use semilog_macros::Semilattice;
 
 mod datalog;
 mod guarded_pair;
+mod immutable;
 mod ord;
 mod pair;
 mod redactable;
+mod register;
 
+#[cfg(feature = "alloc")]
+mod clock;
+#[cfg(feature = "alloc")]
+mod counter;
 #[cfg(feature = "alloc")]
 mod map;
 #[cfg(feature = "alloc")]
+mod mv_register;
+#[cfg(feature = "alloc")]
+mod or_map;
+#[cfg(feature = "alloc")]
 mod set;
 #[cfg(feature = "alloc")]
+mod two_phase_set;
+#[cfg(feature = "alloc")]
 mod vec;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub use {
     datalog::{DeferredRestore, Iteration, Simple},
     guarded_pair::GuardedPair,
-    ord::{Interval, Max, Min},
+    immutable::Immutable,
+    ord::{Greatest, Interval, Least, Max, Min},
     pair::Pair,
     redactable::Redactable,
+    register::LWWRegister,
 };
 
 #[cfg(feature = "alloc")]
 pub use {
+    clock::VectorClock,
+    counter::{GCounter, PNCounter},
     map::{Map, MapLattice},
+    mv_register::MVRegister,
+    or_map::{ORMapEntry, ORMapLattice},
     set::{Set, SetLattice},
+    two_phase_set::TwoPhaseSetLattice,
     vec::VecLattice,
 };
 
@@ -42,6 +63,11 @@ pub use {
 pub trait Semilattice: Default + PartialOrd {
     fn join(self, other: Self) -> Self;
 
+    /// The least element of the lattice, `Default` unless stated otherwise.
+    fn bottom() -> Self {
+        Self::default()
+    }
+
     fn join_assign(&mut self, other: Self) {
         *self = mem::take(self).join(other);
     }
@@ -51,7 +77,23 @@ impl Semilattice for () {
     fn join(self, _: Self) -> Self {}
 }
 
-/// Reduce an iterator of semilattice values to its least upper bound.
+/// `None` is the bottom element, which is less than any `Some`.
+impl<T> Semilattice for Option<T>
+where
+    T: Semilattice,
+{
+    fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (Some(a), Some(b)) => Some(a.join(b)),
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+}
+
+/// Reduce an iterator of semilattice values to its least upper bound, which
+/// is the bottom element if the iterator is empty.
+#[doc(alias = "join_all")]
 pub fn fold<S>(i: impl IntoIterator<Item = S>) -> S
 where
     S: Semilattice,
@@ -126,6 +168,11 @@ pub fn partially_verify_semilattice_laws<S: Semilattice + fmt::Debug + Clone>(
 
 /// A helper function intended for `core::cmp::PartialOrd::partial_cmp`. This
 /// is used by the derive macro `#[derive(Semilattice)]`.
+///
+/// Combines the orderings of each field into the product order: `Equal` if
+/// every field is equal, `Less` or `Greater` if every field is either equal or
+/// respectively less or greater, and otherwise `None`. Returns as soon as two
+/// fields disagree or one is incomparable, without consuming the rest.
 pub fn partial_ord_helper(
     orders: impl IntoIterator<Item = Option<cmp::Ordering>>,
 ) -> Option<cmp::Ordering> {
@@ -143,3 +190,85 @@ pub fn partial_ord_helper(
 
     Some(greater.cmp(&less))
 }
+
+/// Whether neither state is less than or equal to the other, so that each
+/// has seen changes which the other has not.
+pub fn is_concurrent<T: PartialOrd>(a: &T, b: &T) -> bool {
+    a.partial_cmp(b).is_none()
+}
+
+/// Whether `a` has seen every change which `b` has.
+pub fn dominates<T: PartialOrd>(a: &T, b: &T) -> bool {
+    a >= b
+}
+
+/// Whether `b` has seen every change which `a` has.
+pub fn dominated_by<T: PartialOrd>(a: &T, b: &T) -> bool {
+    a <= b
+}
+
+#[test]
+fn check_option_laws() {
+    assert_eq!(Some(Max(1)).join(None), Some(Max(1)));
+    assert_eq!(Some(Max(1)).join(Some(Max(2))), Some(Max(2)));
+
+    partially_verify_semilattice_laws([None, Some(Max(0)), Some(Max(1))]);
+    partially_verify_semilattice_laws([
+        None,
+        Some(Pair(Min(0), Max(0))),
+        Some(Pair(Min(1), Max(1))),
+    ]);
+}
+
+#[test]
+fn check_concurrency() {
+    let a = Pair(Max(1), Max(0));
+    let b = Pair(Max(0), Max(1));
+    let ab = a.join(b);
+
+    assert!(!is_concurrent(&a, &a));
+    assert!(dominates(&a, &a) && dominated_by(&a, &a));
+
+    assert!(!is_concurrent(&a, &ab));
+    assert!(dominates(&ab, &a) && !dominates(&a, &ab));
+    assert!(dominated_by(&a, &ab) && !dominated_by(&ab, &a));
+
+    assert!(is_concurrent(&a, &b));
+    assert!(!dominates(&a, &b) && !dominated_by(&a, &b));
+}
+
+#[test]
+fn check_partial_ord_helper() {
+    use cmp::Ordering::{Equal, Greater, Less};
+
+    assert_eq!(partial_ord_helper([]), Some(Equal));
+    assert_eq!(partial_ord_helper([Some(Equal), Some(Equal)]), Some(Equal));
+    assert_eq!(partial_ord_helper([Some(Equal), Some(Less)]), Some(Less));
+    assert_eq!(
+        partial_ord_helper([Some(Greater), Some(Equal)]),
+        Some(Greater)
+    );
+    assert_eq!(partial_ord_helper([Some(Less), Some(Less)]), Some(Less));
+    assert_eq!(partial_ord_helper([Some(Less), Some(Greater)]), None);
+    assert_eq!(partial_ord_helper([Some(Equal), None]), None);
+
+    // Orderings after the first conflict are not consumed.
+    let mut orders = [Some(Greater), Some(Less), Some(Equal)].into_iter();
+    assert_eq!(partial_ord_helper(&mut orders), None);
+    assert_eq!(orders.next(), Some(Some(Equal)));
+}
+
+#[test]
+fn check_fold() {
+    assert_eq!(fold::<Max<u8>>([]), Max(0));
+    assert_eq!(fold::<Option<Max<u8>>>([]), None);
+    assert_eq!(fold([Pair(Min(3), Max(3))]), Pair(Min(3), Max(3)));
+    assert_eq!(
+        fold([
+            Pair(Min(3), Max(3)),
+            Pair(Min(1), Max(1)),
+            Pair(Min(2), Max(5))
+        ]),
+        Pair(Min(1), Max(5))
+    );
+}
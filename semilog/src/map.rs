@@ -1,4 +1,4 @@
-use alloc::{borrow::ToOwned, vec, vec::Vec};
+use alloc::{borrow::ToOwned, collections::BTreeMap, vec, vec::Vec};
 use core::{borrow::Borrow, cmp, mem, ops};
 
 use crate::{DeferredRestore, Semilattice};
@@ -28,7 +28,11 @@ fn gallop<T>(mut slice: &[T], mut cmp: impl FnMut(&T) -> bool) -> &[T] {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 #[cfg_attr(
     feature = "minicbor",
     derive(minicbor::Encode, minicbor::Decode),
@@ -68,6 +72,83 @@ where
         }
     }
 
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.entry(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.entry(key).is_some()
+    }
+
+    /// Like `get`, but falls back to `default` for a missing key, which is not
+    /// inserted.
+    pub fn get_or<'a, Q>(&'a self, key: &Q, default: &'a V) -> &'a V
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.entry(key).unwrap_or(default)
+    }
+
+    /// Unlike `entry_mut`, a missing key is not inserted.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        match self.inner.binary_search_by(|(k, _)| k.borrow().cmp(key)) {
+            Ok(i) => Some(&mut self.inner[i].1),
+            _ => None,
+        }
+    }
+
+    /// The entry with the least key, if any.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.inner.first().map(|(k, v)| (k, v))
+    }
+
+    /// The entry with the greatest key, if any.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.inner.last().map(|(k, v)| (k, v))
+    }
+
+    pub fn iter(&self) -> <&Self as IntoIterator>::IntoIter {
+        self.into_iter()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.inner.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.inner.iter().map(|(_, v)| v)
+    }
+
+    /// The entries of `self` which `other` lacks or does not dominate, such
+    /// that joining the result into `other` equals joining `self` into `other`.
+    pub fn delta_since(&self, other: &Self) -> Self
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Self {
+            inner: self
+                .inner
+                .iter()
+                .filter(|(k, v)| !matches!(other.get(k), Some(o) if v <= o))
+                .cloned()
+                .collect(),
+        }
+    }
+
     pub fn entry_mut<Q>(&mut self, key: &Q) -> &mut V
     where
         K: Borrow<Q>,
@@ -85,6 +166,36 @@ where
     }
 }
 
+impl<K, V> MapLattice<K, V> {
+    /// The number of keys.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Keep only the entries for which `f` returns `true`, in order.
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+        self.inner.retain(|(k, v)| f(k, v));
+    }
+}
+
+impl<K, V> MapLattice<K, V>
+where
+    V: Semilattice + PartialEq,
+{
+    /// Drop the entries whose values are bottom. Since joining with bottom
+    /// is a no-op, and a missing key reads as bottom through `get_or` and
+    /// `entry_mut`, this drops keys but never values. The keys are still part
+    /// of the order, though, so the pruned map is less than the original.
+    pub fn prune_bottom(&mut self) {
+        let bottom = V::bottom();
+        self.retain(|_, v| *v != bottom);
+    }
+}
+
 impl<K, V> From<Vec<(K, V)>> for MapLattice<K, V>
 where
     K: Ord,
@@ -96,6 +207,27 @@ where
     }
 }
 
+/// `BTreeMap` cannot itself be a `Semilattice`, as its `PartialOrd` is
+/// lexicographic rather than the lattice order. Convert it instead; the values
+/// must themselves be semilattices.
+impl<K, V> From<BTreeMap<K, V>> for MapLattice<K, V> {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        // Already sorted and free of duplicates.
+        Self {
+            inner: map.into_iter().collect(),
+        }
+    }
+}
+
+impl<K, V> From<MapLattice<K, V>> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(map: MapLattice<K, V>) -> Self {
+        map.inner.into_iter().collect()
+    }
+}
+
 impl<K, V> ops::Deref for MapLattice<K, V> {
     type Target = Vec<(K, V)>;
 
@@ -129,7 +261,7 @@ where
 
         // FIXME: optimize using gallop
         for (k1, _) in &self.inner {
-            if let None = other.entry(k1) {
+            if other.entry(k1).is_none() {
                 // other is missing a key from self
                 greater = true;
             }
@@ -169,39 +301,63 @@ where
             None => {
                 self.inner.append(&mut other.inner);
 
-                // sort is faster than unstable_sort with sequences of sorted tuples.
-                self.inner.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
-                let (dedup, dups) = self.inner.partition_dedup_by(|(k1, _), (k2, _)| k1 == k2);
-
-                // merge all non-ZST values.
-                if mem::size_of::<V>() > 0 {
-                    // partition_dedup_by maintains the order of `dedup` but does
-                    // not define the order of `dups`.
-                    for dup in dups {
-                        dedup[dedup
-                            .binary_search_by(|x| x.0.cmp(&dup.0))
-                            .expect("dedup contains dups by definition")]
-                        .1
-                        .join_assign(core::mem::take(&mut dup.1));
-                    }
-                }
-
-                let len = dedup.len();
-                self.inner.truncate(len);
-
-                self
+                Self::from_iter(self.inner)
             }
         }
     }
 }
 
+impl<K, V> IntoIterator for MapLattice<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, K: 'a, V: 'a> IntoIterator for &'a MapLattice<K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[allow(clippy::type_complexity)]
+    type IntoIter =
+        core::iter::Map<core::slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter().map(|(k, v)| (k, v))
+    }
+}
+
 impl<K, V> FromIterator<(K, V)> for MapLattice<K, V>
 where
     K: Ord,
     V: Semilattice,
 {
+    /// Values of duplicate keys are joined.
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        Self::from(Vec::from_iter(iter))
+        let mut inner = Vec::from_iter(iter);
+        // sort is faster than unstable_sort with sequences of sorted tuples.
+        inner.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        // `dedup_by` removes `later`, having joined it into `earlier`.
+        inner.dedup_by(|later, earlier| {
+            let dup = later.0 == earlier.0;
+            if dup {
+                earlier.1.join_assign(mem::take(&mut later.1));
+            }
+            dup
+        });
+
+        Self { inner }
+    }
+}
+
+impl<K, V> Extend<(K, V)> for MapLattice<K, V>
+where
+    K: Ord,
+    V: Semilattice,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.join_assign(Self::from_iter(iter));
     }
 }
 
@@ -285,20 +441,15 @@ where
                     vec.append(&mut other);
                     vec.sort_by(|x, y| x.0.cmp(&y.0));
 
-                    let (dedup, dups) = vec.partition_dedup_by(|x, y| x.0 == y.0);
-
-                    // partition_dedup_by maintains the order of `dedup` but
-                    // does not define the order of `dups`.
-                    for dup in dups {
-                        dedup[dedup
-                            .binary_search_by(|x| x.0.cmp(&dup.0))
-                            .expect("dedup contains dups by definition")]
-                        .1
-                        .join_assign(core::mem::take(&mut dup.1));
-                    }
-
-                    let len = dedup.len();
-                    vec.truncate(len);
+                    // `dedup_by` removes `later`, having joined it into
+                    // `earlier`.
+                    vec.dedup_by(|later, earlier| {
+                        let dup = later.0 == earlier.0;
+                        if dup {
+                            earlier.1.join_assign(core::mem::take(&mut later.1));
+                        }
+                        dup
+                    });
 
                     vec
                 }
@@ -376,3 +527,137 @@ fn check_laws() {
 
     partially_verify_semilattice_laws([a, b, c, d]);
 }
+
+#[test]
+fn check_iter() {
+    use crate::Max;
+
+    let mut a = MapLattice::from_iter([("Bob", Max(50)), ("Alice", Max(123))]);
+
+    assert_eq!(a.get("Alice"), Some(&Max(123)));
+    assert_eq!(a.get("Carol"), None);
+    assert!(a.contains_key("Alice") && !a.contains_key("Carol"));
+    assert_eq!(a.keys().collect::<Vec<_>>(), [&"Alice", &"Bob"]);
+    assert_eq!(a.values().collect::<Vec<_>>(), [&Max(123), &Max(50)]);
+    assert_eq!(
+        a.iter().collect::<Vec<_>>(),
+        [(&"Alice", &Max(123)), (&"Bob", &Max(50))]
+    );
+    // Lookups do not insert missing keys.
+    assert_eq!(a.get_or("Carol", &Max(0)), &Max(0));
+    assert_eq!(a.get_or("Alice", &Max(0)), &Max(123));
+    assert_eq!(a.get_mut("Carol"), None);
+    assert_eq!(a.len(), 2);
+    a.get_mut("Bob").unwrap().join_assign(Max(60));
+    assert_eq!(a.get("Bob"), Some(&Max(60)));
+
+    assert_eq!(
+        a.into_iter().collect::<Vec<_>>(),
+        [("Alice", Max(123)), ("Bob", Max(60))]
+    );
+}
+
+#[test]
+fn check_duplicates() {
+    use crate::Max;
+
+    let mut a = MapLattice::from_iter([("Bob", Max(50)), ("Alice", Max(1)), ("Bob", Max(70))]);
+    assert_eq!(a.inner, [("Alice", Max(1)), ("Bob", Max(70))]);
+
+    a.extend([("Carol", Max(3)), ("Alice", Max(2)), ("Carol", Max(2))]);
+    assert_eq!(
+        a.inner,
+        [("Alice", Max(2)), ("Bob", Max(70)), ("Carol", Max(3))]
+    );
+}
+
+#[test]
+fn check_len() {
+    use crate::Max;
+
+    let mut a = MapLattice::default();
+    assert!(a.is_empty());
+    assert_eq!(a.len(), 0);
+
+    a.insert("Alice", Max(1));
+    a.insert("Alice", Max(2));
+    assert!(!a.is_empty());
+    assert_eq!(a.len(), 1);
+}
+
+#[test]
+fn check_first_last() {
+    use crate::Max;
+
+    let mut a = MapLattice::default();
+    assert_eq!(a.first_key_value(), None);
+    assert_eq!(a.last_key_value(), None);
+
+    a.insert("Bob", Max(50));
+    assert_eq!(a.first_key_value(), Some((&"Bob", &Max(50))));
+    assert_eq!(a.last_key_value(), Some((&"Bob", &Max(50))));
+
+    a.insert("Carol", Max(7));
+    a.insert("Alice", Max(123));
+    assert_eq!(a.first_key_value(), Some((&"Alice", &Max(123))));
+    assert_eq!(a.last_key_value(), Some((&"Carol", &Max(7))));
+}
+
+#[test]
+fn check_btree_map() {
+    use crate::Max;
+
+    let a = BTreeMap::from([("Alice", Max(123)), ("Bob", Max(50))]);
+    let b = BTreeMap::from([("Bob", Max(300)), ("Carol", Max(100))]);
+
+    let ab = MapLattice::from(a.clone()).join(MapLattice::from(b.clone()));
+    let ba = MapLattice::from(b).join(MapLattice::from(a));
+    assert_eq!(ab, ba);
+    assert_eq!(
+        BTreeMap::from(ab),
+        BTreeMap::from([("Alice", Max(123)), ("Bob", Max(300)), ("Carol", Max(100))])
+    );
+}
+
+#[test]
+fn check_delta() {
+    use crate::Max;
+
+    let a = MapLattice::from_iter([("Alice", Max(123)), ("Bob", Max(50)), ("Carol", Max(7))]);
+    let b = MapLattice::from_iter([("Bob", Max(300)), ("Carol", Max(7))]);
+
+    let delta = a.delta_since(&b);
+    assert_eq!(delta, MapLattice::from_iter([("Alice", Max(123))]));
+    assert_eq!(b.clone().join(delta), b.clone().join(a.clone()));
+
+    let delta = b.delta_since(&a);
+    assert_eq!(delta, MapLattice::from_iter([("Bob", Max(300))]));
+    assert_eq!(a.clone().join(delta), a.clone().join(b));
+
+    assert_eq!(a.delta_since(&a), MapLattice::default());
+}
+
+#[test]
+fn check_prune() {
+    use crate::{Max, SetLattice};
+
+    let mut a = MapLattice::from_iter([("Alice", Max(1)), ("Bob", Max(2)), ("Carol", Max(3))]);
+    a.retain(|k, v| *k != "Alice" && v.0 < 3);
+    assert_eq!(a, MapLattice::singleton("Bob", Max(2)));
+
+    let mut b = MapLattice::from_iter([
+        ("Alice", SetLattice::default()),
+        ("Bob", SetLattice::singleton(1)),
+    ]);
+    let before = b.clone();
+    b.prune_bottom();
+    assert_eq!(b, MapLattice::singleton("Bob", SetLattice::singleton(1)));
+
+    // Pruning drops keys, but no values.
+    let empty = SetLattice::default();
+    for key in ["Alice", "Bob"] {
+        assert_eq!(b.get_or(key, &empty), before.get_or(key, &empty));
+    }
+    assert!(b < before);
+    assert_eq!(b.join(before.clone()), before);
+}
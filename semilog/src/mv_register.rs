@@ -0,0 +1,95 @@
+use crate::{self as semilog, GuardedPair, Max, Semilattice, SetLattice};
+
+/// A multi-value register. Writes are guarded by a clock: a write with a
+/// greater clock replaces all values, while concurrent writes with an equal
+/// clock are all retained until a later write supersedes them.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "T: Ord")]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[cfg_attr(
+    feature = "minicbor",
+    derive(minicbor::Encode, minicbor::Decode),
+    cbor(transparent)
+)]
+pub struct MVRegister<T> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub inner: GuardedPair<Max<u64>, SetLattice<T>>,
+}
+
+impl<T> Default for MVRegister<T> {
+    fn default() -> Self {
+        Self {
+            inner: GuardedPair {
+                guard: Max(0),
+                value: SetLattice::default(),
+            },
+        }
+    }
+}
+
+impl<T> MVRegister<T>
+where
+    T: Ord,
+{
+    pub fn singleton(clock: u64, value: T) -> Self {
+        Self {
+            inner: GuardedPair {
+                guard: Max(clock),
+                value: SetLattice::singleton(value),
+            },
+        }
+    }
+
+    /// Write a value. To supersede the current values, the clock must be
+    /// greater than `MVRegister::clock`.
+    pub fn write(&mut self, clock: u64, value: T) {
+        self.join_assign(Self::singleton(clock, value));
+    }
+
+    pub fn clock(&self) -> u64 {
+        self.inner.guard.0
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.inner.value.into_iter()
+    }
+}
+
+#[test]
+fn check_laws() {
+    use alloc::vec::Vec;
+
+    use crate::partially_verify_semilattice_laws;
+
+    let a = MVRegister::singleton(1, "Issue with feature X");
+
+    // Two concurrent edits are both retained...
+    let mut b = a.clone();
+    b.write(2, "Issue with feature Y");
+    let mut c = a.clone();
+    c.write(2, "Issue with feature Z");
+
+    let mut d = b.clone().join(c.clone());
+    assert_eq!(
+        d.values().collect::<Vec<_>>(),
+        [&"Issue with feature Y", &"Issue with feature Z"]
+    );
+
+    // ...until a later edit resolves them.
+    d.write(d.clock() + 1, "Issue with features Y and Z");
+    assert_eq!(
+        d.values().collect::<Vec<_>>(),
+        [&"Issue with features Y and Z"]
+    );
+
+    // Stale writes are ignored.
+    d.write(0, "Issue");
+    assert_eq!(d.clock(), 3);
+    assert_eq!(d.values().count(), 1);
+
+    partially_verify_semilattice_laws([a, b, c, d]);
+}
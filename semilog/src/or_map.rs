@@ -0,0 +1,162 @@
+use core::borrow::Borrow;
+
+use crate::{self as semilog, MapLattice, Semilattice, SetLattice};
+
+/// The state of a key within an `ORMapLattice`. The key is present while any
+/// tag which added it has not been observed by a removal.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "T: Ord, V: Semilattice")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub struct ORMapEntry<T, V> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub added: SetLattice<T>,
+    #[cfg_attr(feature = "minicbor", n(1))]
+    pub removed: SetLattice<T>,
+    #[cfg_attr(feature = "minicbor", n(2))]
+    pub value: V,
+}
+
+impl<T, V> Default for ORMapEntry<T, V>
+where
+    V: Default,
+{
+    fn default() -> Self {
+        Self {
+            added: SetLattice::default(),
+            removed: SetLattice::default(),
+            value: V::default(),
+        }
+    }
+}
+
+impl<T, V> ORMapEntry<T, V>
+where
+    T: Ord,
+{
+    pub fn is_present(&self) -> bool {
+        self.added
+            .into_iter()
+            .any(|tag| self.removed.entry(tag).is_none())
+    }
+}
+
+/// An observed-remove map. Each insertion is labelled with a unique tag, such
+/// as an actor paired with a counter. A removal only applies to the tags it has
+/// observed, thus a concurrent insertion survives the removal.
+///
+/// The values of removed keys are retained, and are joined with any value
+/// inserted thereafter.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "K: Ord, T: Ord, V: Semilattice")]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[cfg_attr(
+    feature = "minicbor",
+    derive(minicbor::Encode, minicbor::Decode),
+    cbor(transparent)
+)]
+pub struct ORMapLattice<K, T, V> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub inner: MapLattice<K, ORMapEntry<T, V>>,
+}
+
+impl<K, T, V> Default for ORMapLattice<K, T, V> {
+    fn default() -> Self {
+        Self {
+            inner: MapLattice::default(),
+        }
+    }
+}
+
+impl<K, T, V> ORMapLattice<K, T, V>
+where
+    K: Ord,
+    T: Ord,
+    V: Semilattice,
+{
+    pub fn insert(&mut self, tag: T, key: K, val: V) {
+        self.inner.insert(
+            key,
+            ORMapEntry {
+                added: SetLattice::singleton(tag),
+                removed: SetLattice::default(),
+                value: val,
+            },
+        );
+    }
+
+    /// Remove the key, as observed by this replica.
+    pub fn remove<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        T: Clone,
+    {
+        if let Ok(i) = self.inner.binary_search_by(|(k, _)| k.borrow().cmp(key)) {
+            let entry = &mut self.inner[i].1;
+            let added = entry.added.clone();
+            entry.removed.join_assign(added);
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.inner
+            .entry(key)
+            .filter(|entry| entry.is_present())
+            .map(|entry| &entry.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterate over the keys which are present, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.inner
+            .iter()
+            .filter(|(_, entry)| entry.is_present())
+            .map(|(k, entry)| (k, &entry.value))
+    }
+}
+
+#[test]
+fn check_laws() {
+    use crate::{partially_verify_semilattice_laws, Max};
+
+    let mut a = ORMapLattice::default();
+    a.insert(("alice", 0), "thread", Max(1));
+
+    // Bob observes Alice's insertion, then removes the key.
+    let mut b = a.clone();
+    b.remove("thread");
+    assert!(!b.contains_key("thread"));
+
+    // Concurrently, Carol inserts the key again.
+    let mut c = a.clone();
+    c.insert(("carol", 0), "thread", Max(2));
+
+    // The removal wins over the insertion it observed...
+    assert!(!a.clone().join(b.clone()).contains_key("thread"));
+    // ...but not over the concurrent insertion.
+    let d = b.clone().join(c.clone());
+    assert_eq!(d, c.clone().join(b.clone()));
+    assert_eq!(d.get("thread"), Some(&Max(2)));
+    assert_eq!(
+        d.iter().collect::<alloc::vec::Vec<_>>(),
+        [(&"thread", &Max(2))]
+    );
+
+    partially_verify_semilattice_laws([a, b, c, d]);
+}
@@ -2,6 +2,58 @@ use core::{cmp, ops};
 
 use crate::{partial_ord_helper, Semilattice};
 
+/// A totally ordered type with a least element, the bottom of `Max`.
+pub trait Least: Ord {
+    fn least() -> Self;
+}
+
+/// A totally ordered type with a greatest element, the bottom of `Min`.
+pub trait Greatest: Ord {
+    fn greatest() -> Self;
+}
+
+macro_rules! bounded {
+    ($($ty:ty),*) => {$(
+        impl Least for $ty {
+            fn least() -> Self {
+                <$ty>::MIN
+            }
+        }
+
+        impl Greatest for $ty {
+            fn greatest() -> Self {
+                <$ty>::MAX
+            }
+        }
+    )*};
+}
+
+bounded!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char);
+
+/// `None` is less than any `Some`, so `Max<Option<T>>` starts out empty.
+impl<T> Least for Option<T>
+where
+    T: Ord,
+{
+    fn least() -> Self {
+        None
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Least for chrono::DateTime<chrono::Utc> {
+    fn least() -> Self {
+        Self::MIN_UTC
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Greatest for chrono::DateTime<chrono::Utc> {
+    fn greatest() -> Self {
+        Self::MAX_UTC
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
@@ -9,10 +61,10 @@ pub struct Max<T>(#[cfg_attr(feature = "minicbor", n(0))] pub T);
 
 impl<T> Default for Max<T>
 where
-    T: num_traits::bounds::Bounded,
+    T: Least,
 {
     fn default() -> Self {
-        Self(T::min_value())
+        Self(T::least())
     }
 }
 
@@ -32,7 +84,7 @@ impl<T> From<T> for Max<T> {
 
 impl<T> Semilattice for Max<T>
 where
-    T: num_traits::bounds::Bounded + Ord,
+    T: Least,
 {
     fn join(self, other: Self) -> Self {
         Self(self.0.max(other.0))
@@ -47,10 +99,10 @@ pub struct Min<T>(#[cfg_attr(feature = "minicbor", n(0))] pub T);
 
 impl<T> Default for Min<T>
 where
-    T: num_traits::bounds::Bounded,
+    T: Greatest,
 {
     fn default() -> Self {
-        Self(T::max_value())
+        Self(T::greatest())
     }
 }
 
@@ -79,7 +131,7 @@ where
 
 impl<T> Semilattice for Min<T>
 where
-    T: num_traits::bounds::Bounded + Ord,
+    T: Greatest,
 {
     fn join(self, other: Self) -> Self {
         Self(self.0.min(other.0))
@@ -94,19 +146,19 @@ pub struct Interval<T> {
 
 impl<T> Interval<T>
 where
-    T: num_traits::bounds::Bounded,
+    T: Least + Greatest,
 {
     fn top() -> Self {
         Self {
-            lower: Max(T::max_value()),
-            upper: Min(T::min_value()),
+            lower: Max(T::greatest()),
+            upper: Min(T::least()),
         }
     }
 }
 
 impl<T> Default for Interval<T>
 where
-    T: num_traits::bounds::Bounded,
+    T: Least + Greatest,
 {
     fn default() -> Self {
         Self {
@@ -118,7 +170,7 @@ where
 
 impl<T> From<(T, T)> for Interval<T>
 where
-    T: num_traits::bounds::Bounded + Ord,
+    T: Least + Greatest,
 {
     fn from((lower, upper): (T, T)) -> Self {
         if lower <= upper {
@@ -134,7 +186,7 @@ where
 
 impl<T> cmp::PartialOrd for Interval<T>
 where
-    T: num_traits::bounds::Bounded + Ord,
+    T: Least + Greatest,
 {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
         partial_ord_helper([
@@ -146,7 +198,7 @@ where
 
 impl<T> Semilattice for Interval<T>
 where
-    T: num_traits::bounds::Bounded + Ord,
+    T: Least + Greatest,
 {
     fn join(self, other: Self) -> Self {
         let lower = self.lower.join(other.lower);
@@ -160,3 +212,48 @@ where
         }
     }
 }
+
+#[test]
+fn check_laws() {
+    use crate::partially_verify_semilattice_laws;
+
+    assert_eq!(Min(3).join(Min(5)), Min(3));
+    assert_eq!(Max(3).join(Max(5)), Max(5));
+    assert!(Min(3) > Min(5));
+    assert_eq!(Min::<u8>::default(), Min(u8::MAX));
+
+    partially_verify_semilattice_laws((-5..5).map(Min));
+    partially_verify_semilattice_laws((-5..5).map(Max));
+}
+
+#[test]
+fn check_option() {
+    use crate::partially_verify_semilattice_laws;
+
+    assert_eq!(Max::<Option<u8>>::default(), Max(None));
+    assert_eq!(Max(None).join(Max(Some(0))), Max(Some(0)));
+    assert_eq!(Max(Some(3)).join(Max(Some(5))), Max(Some(5)));
+
+    partially_verify_semilattice_laws([None, Some(-5), Some(0), Some(5)].map(Max));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn check_chrono() {
+    use chrono::{TimeZone, Utc};
+
+    use crate::partially_verify_semilattice_laws;
+
+    let earlier = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+    let later = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+
+    assert_eq!(Max(earlier).join(Max(later)), Max(later));
+    assert_eq!(Min(earlier).join(Min(later)), Min(earlier));
+    assert_eq!(
+        Max::<chrono::DateTime<Utc>>::default().join(Max(earlier)),
+        Max(earlier)
+    );
+
+    partially_verify_semilattice_laws([earlier, later].map(Max));
+    partially_verify_semilattice_laws([earlier, later].map(Min));
+}
@@ -1,7 +1,11 @@
 use crate::{self as semilog, Semilattice};
 
 /// An anonymous pair of semilattices.
+///
+/// Tuples cannot be semilattices, because their `PartialOrd` is lexicographic
+/// rather than element-wise. Use `Pair`, or `HList!` for more elements.
 #[derive(Clone, Copy, Default, Debug, PartialEq, Semilattice)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
 pub struct Pair<A, B>(
     #[cfg_attr(feature = "minicbor", n(0))] pub A,
@@ -41,3 +45,20 @@ fn check_laws() {
         Pair(Min(-5), Max(4))
     );
 }
+
+#[cfg(feature = "alloc")]
+#[test]
+fn check_join_element_wise() {
+    use crate::{partially_verify_semilattice_laws, Max, SetLattice};
+
+    let a = Pair(Max(1u64), SetLattice::singleton("Issue with feature X"));
+    let b = Pair(Max(2u64), SetLattice::singleton("Issue with feature Y"));
+    assert_eq!(
+        a.clone().join(b.clone()),
+        Pair(
+            Max(2),
+            SetLattice::from_iter(["Issue with feature X", "Issue with feature Y"])
+        )
+    );
+    partially_verify_semilattice_laws([a, b]);
+}
@@ -5,11 +5,12 @@ use crate::Semilattice;
 /// Redactable data. The contained data is arbitrary, not a semilattice. Any
 /// attempts to change the underlying value, will collapse to the redacted
 /// state.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
 pub enum Redactable<T> {
     // FIXME: It is syntactically invalid to use this variant.
+    #[default]
     #[cfg_attr(feature = "minicbor", n(0))]
     Uninitialized,
     #[cfg_attr(feature = "minicbor", n(1))]
@@ -18,9 +19,26 @@ pub enum Redactable<T> {
     Redacted,
 }
 
-impl<T> Default for Redactable<T> {
-    fn default() -> Self {
-        Self::Uninitialized
+impl<T> Redactable<T> {
+    /// The data, unless it was redacted or is uninitialized.
+    pub fn as_data(&self) -> Option<&T> {
+        match self {
+            Self::Data(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    pub fn is_redacted(&self) -> bool {
+        matches!(self, Self::Redacted)
+    }
+
+    /// Transform the data, if any, keeping the state otherwise.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Redactable<U> {
+        match self {
+            Self::Uninitialized => Redactable::Uninitialized,
+            Self::Data(data) => Redactable::Data(f(data)),
+            Self::Redacted => Redactable::Redacted,
+        }
     }
 }
 
@@ -65,3 +83,20 @@ fn check_laws() {
 
     partially_verify_semilattice_laws([Redacted, Data("Hello world."), Data("Hello kitty.")]);
 }
+
+#[test]
+fn check_helpers() {
+    use Redactable::{Data, Redacted, Uninitialized};
+
+    assert_eq!(Data(1).as_data(), Some(&1));
+    assert_eq!(Redacted::<u8>.as_data(), None);
+    assert_eq!(Uninitialized::<u8>.as_data(), None);
+
+    assert!(!Data(1).is_redacted());
+    assert!(Redacted::<u8>.is_redacted());
+    assert!(!Uninitialized::<u8>.is_redacted());
+
+    assert_eq!(Data(1).map(|x| x + 1), Data(2));
+    assert_eq!(Redacted::<u8>.map(|x| x + 1), Redacted);
+    assert_eq!(Uninitialized::<u8>.map(|x| x + 1), Uninitialized);
+}
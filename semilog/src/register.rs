@@ -0,0 +1,79 @@
+use core::cmp::{Ordering, PartialOrd};
+
+use crate::Semilattice;
+
+/// A last-writer-wins register. Writes are ordered by their timestamp, ties
+/// are broken by the writing actor, and lastly by the value itself, such that
+/// concurrent writes never fork.
+///
+/// The default actor and value are expected to be their least elements, as is
+/// the case for strings and unsigned integers.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub struct LWWRegister<A, T> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub timestamp: u64,
+    #[cfg_attr(feature = "minicbor", n(1))]
+    pub actor: A,
+    #[cfg_attr(feature = "minicbor", n(2))]
+    pub value: T,
+}
+
+impl<A, T> LWWRegister<A, T> {
+    pub fn new(timestamp: u64, actor: A, value: T) -> Self {
+        Self {
+            timestamp,
+            actor,
+            value,
+        }
+    }
+}
+
+impl<A, T> Ord for LWWRegister<A, T>
+where
+    A: Ord,
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.timestamp, &self.actor, &self.value).cmp(&(
+            other.timestamp,
+            &other.actor,
+            &other.value,
+        ))
+    }
+}
+
+impl<A, T> PartialOrd for LWWRegister<A, T>
+where
+    A: Ord,
+    T: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A, T> Semilattice for LWWRegister<A, T>
+where
+    A: Default + Ord,
+    T: Default + Ord,
+{
+    fn join(self, other: Self) -> Self {
+        self.max(other)
+    }
+}
+
+#[test]
+fn check_laws() {
+    use crate::partially_verify_semilattice_laws;
+
+    let a = LWWRegister::new(1, "alice", "Hello world.");
+    let b = LWWRegister::new(1, "bob", "Hello kitty.");
+    let c = LWWRegister::new(2, "alice", "Goodbye.");
+
+    assert_eq!(a.clone().join(b.clone()), b);
+    assert_eq!(b.clone().join(c.clone()), c);
+
+    partially_verify_semilattice_laws([a, b, c, LWWRegister::new(2, "alice", "Farewell.")]);
+}
@@ -1,9 +1,15 @@
-use core::{cmp, ops};
+use core::{borrow::Borrow, cmp, ops};
+
+use alloc::collections::BTreeSet;
 
 use crate::{DeferredRestore, Map, MapLattice, Semilattice};
 
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 #[cfg_attr(
     feature = "minicbor",
     derive(minicbor::Encode, minicbor::Decode),
@@ -27,6 +33,52 @@ where
     pub fn insert(&mut self, val: V) {
         self.inner.insert(val, ());
     }
+
+    pub fn contains<Q>(&self, val: &Q) -> bool
+    where
+        V: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.inner.contains_key(val)
+    }
+
+    /// The elements which are in this set but not in `other`, in order.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a V> {
+        self.into_iter().filter(move |val| !other.contains(*val))
+    }
+}
+
+impl<V> SetLattice<V> {
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// `BTreeSet` cannot itself be a `Semilattice`, as its `PartialOrd` is
+/// lexicographic rather than by inclusion. Convert it instead.
+impl<V> From<BTreeSet<V>> for SetLattice<V> {
+    fn from(set: BTreeSet<V>) -> Self {
+        // Already sorted and free of duplicates.
+        Self {
+            inner: MapLattice {
+                inner: set.into_iter().map(|v| (v, ())).collect(),
+            },
+        }
+    }
+}
+
+impl<V> From<SetLattice<V>> for BTreeSet<V>
+where
+    V: Ord,
+{
+    fn from(set: SetLattice<V>) -> Self {
+        set.inner.inner.into_iter().map(|(v, _)| v).collect()
+    }
 }
 
 impl<V> ops::Deref for SetLattice<V> {
@@ -79,6 +131,15 @@ where
     }
 }
 
+impl<V> Extend<V> for SetLattice<V>
+where
+    V: Ord,
+{
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        self.inner.extend(iter.into_iter().map(|v| (v, ())));
+    }
+}
+
 impl<V> PartialOrd for SetLattice<V>
 where
     V: Ord,
@@ -165,3 +226,66 @@ fn check_laws() {
 
     partially_verify_semilattice_laws([a, b, c, d]);
 }
+
+#[test]
+fn check_duplicates() {
+    let mut a = SetLattice::from_iter(["Bob", "Alice", "Bob"]);
+    assert_eq!(a.inner.inner, [("Alice", ()), ("Bob", ())]);
+
+    a.extend(["Carol", "Alice", "Carol"]);
+    assert_eq!(a.inner.inner, [("Alice", ()), ("Bob", ()), ("Carol", ())]);
+}
+
+#[test]
+fn check_len() {
+    let mut a = SetLattice::default();
+    assert!(a.is_empty());
+    assert_eq!(a.len(), 0);
+
+    a.insert("Alice");
+    a.insert("Alice");
+    assert!(!a.is_empty());
+    assert_eq!(a.len(), 1);
+}
+
+#[test]
+fn check_btree_set() {
+    let a = BTreeSet::from(["Alice", "Bob"]);
+    let b = BTreeSet::from(["Bob", "Carol"]);
+
+    let ab = SetLattice::from(a.clone()).join(SetLattice::from(b.clone()));
+    let ba = SetLattice::from(b).join(SetLattice::from(a));
+    assert_eq!(ab, ba);
+    assert_eq!(
+        BTreeSet::from(ab),
+        BTreeSet::from(["Alice", "Bob", "Carol"])
+    );
+}
+
+#[test]
+fn check_difference() {
+    let a = SetLattice::from_iter(["Alice", "Bob"]);
+    let b = SetLattice::from_iter(["Bob", "Carol"]);
+    let c = SetLattice::from_iter(["Dave"]);
+
+    assert!(a.contains("Alice") && !a.contains("Carol"));
+
+    // Overlapping sets.
+    assert_eq!(
+        a.difference(&b).collect::<BTreeSet<_>>(),
+        BTreeSet::from([&"Alice"])
+    );
+    assert_eq!(
+        b.difference(&a).collect::<BTreeSet<_>>(),
+        BTreeSet::from([&"Carol"])
+    );
+    assert_eq!(a.difference(&a).count(), 0);
+
+    // Disjoint sets.
+    assert_eq!(
+        a.difference(&c).collect::<BTreeSet<_>>(),
+        BTreeSet::from([&"Alice", &"Bob"])
+    );
+    assert_eq!(a.difference(&SetLattice::default()).count(), 2);
+    assert_eq!(SetLattice::default().difference(&a).count(), 0);
+}
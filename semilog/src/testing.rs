@@ -0,0 +1,129 @@
+//! Helpers to check that types obey the semilattice laws, along with
+//! `proptest` strategies for the lattices of this crate.
+
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+use proptest::{
+    arbitrary::{any_with, Arbitrary},
+    collection::{btree_map, btree_set, SizeRange},
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use crate::{GuardedPair, MapLattice, Max, Semilattice, SetLattice};
+
+/// Assert that `join` is idempotent, commutative, and associative for every
+/// combination of `values`, and that it is monotone with respect to the
+/// partial order: the join is an upper bound of both arguments, and joining
+/// preserves the order of ordered arguments.
+pub fn assert_semilattice_laws<T>(values: &[T])
+where
+    T: Semilattice + Clone + fmt::Debug,
+{
+    for a in values {
+        assert_eq!(a.clone().join(a.clone()), *a, "join is not idempotent");
+
+        for b in values {
+            let ab = a.clone().join(b.clone());
+            assert_eq!(ab, b.clone().join(a.clone()), "join is not commutative");
+            assert!(a <= &ab && b <= &ab, "{:?} is not an upper bound", ab);
+
+            for c in values {
+                assert_eq!(
+                    ab.clone().join(c.clone()),
+                    a.clone().join(b.clone().join(c.clone())),
+                    "join is not associative"
+                );
+
+                if a <= b {
+                    assert!(
+                        a.clone().join(c.clone()) <= b.clone().join(c.clone()),
+                        "join is not monotone"
+                    );
+                }
+            }
+        }
+    }
+
+    crate::partially_verify_semilattice_laws(values.iter().cloned());
+}
+
+impl<T> Arbitrary for Max<T>
+where
+    T: Arbitrary + Ord + 'static,
+{
+    type Parameters = T::Parameters;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        any_with::<T>(args).prop_map(Max).boxed()
+    }
+}
+
+impl<G, V> Arbitrary for GuardedPair<G, V>
+where
+    G: Arbitrary + 'static,
+    V: Arbitrary + 'static,
+{
+    type Parameters = (G::Parameters, V::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((guard, value): Self::Parameters) -> Self::Strategy {
+        (any_with::<G>(guard), any_with::<V>(value))
+            .prop_map(|(guard, value)| GuardedPair { guard, value })
+            .boxed()
+    }
+}
+
+impl<V> Arbitrary for SetLattice<V>
+where
+    V: Arbitrary + Ord + 'static,
+{
+    type Parameters = (SizeRange, V::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, args): Self::Parameters) -> Self::Strategy {
+        btree_set(any_with::<V>(args), size)
+            .prop_map(SetLattice::from_iter)
+            .boxed()
+    }
+}
+
+impl<K, V> Arbitrary for MapLattice<K, V>
+where
+    K: Arbitrary + Ord + 'static,
+    V: Arbitrary + Semilattice + 'static,
+{
+    type Parameters = (SizeRange, K::Parameters, V::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, k, v): Self::Parameters) -> Self::Strategy {
+        btree_map(any_with::<K>(k), any_with::<V>(v), size)
+            .prop_map(|map: BTreeMap<K, V>| MapLattice::from_iter(map))
+            .boxed()
+    }
+}
+
+proptest::proptest! {
+    #[test]
+    fn max_laws(values in proptest::array::uniform3(proptest::arbitrary::any::<Max<u8>>())) {
+        assert_semilattice_laws(&values);
+    }
+
+    #[test]
+    fn guarded_pair_laws(
+        values in proptest::array::uniform3(proptest::arbitrary::any::<GuardedPair<Max<u8>, Max<u8>>>())
+    ) {
+        assert_semilattice_laws(&values);
+    }
+
+    #[test]
+    fn set_laws(values in proptest::array::uniform3(proptest::arbitrary::any::<SetLattice<u8>>())) {
+        assert_semilattice_laws(&values);
+    }
+
+    #[test]
+    fn map_laws(values in proptest::array::uniform3(proptest::arbitrary::any::<MapLattice<u8, Max<u8>>>())) {
+        assert_semilattice_laws(&values);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{self as semilog, Semilattice, SetLattice};
+
+/// A two-phase set. Removal is permanent: a removed element stays removed
+/// after joining with any replica which still contains it, and cannot be
+/// inserted again. Both phases only grow, so the set remains monotonic.
+#[derive(Debug, Clone, PartialEq, Semilattice)]
+#[semilattice(bound = "V: Ord")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "minicbor", derive(minicbor::Encode, minicbor::Decode))]
+pub struct TwoPhaseSetLattice<V> {
+    #[cfg_attr(feature = "minicbor", n(0))]
+    pub added: SetLattice<V>,
+    #[cfg_attr(feature = "minicbor", n(1))]
+    pub removed: SetLattice<V>,
+}
+
+impl<V> Default for TwoPhaseSetLattice<V> {
+    fn default() -> Self {
+        Self {
+            added: SetLattice::default(),
+            removed: SetLattice::default(),
+        }
+    }
+}
+
+impl<V> TwoPhaseSetLattice<V>
+where
+    V: Ord,
+{
+    pub fn insert(&mut self, val: V) {
+        self.added.insert(val);
+    }
+
+    pub fn remove(&mut self, val: V) {
+        self.removed.insert(val);
+    }
+
+    pub fn contains(&self, val: &V) -> bool {
+        self.added.contains(val) && !self.removed.contains(val)
+    }
+
+    /// Iterate over the elements which have not been removed, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &V> {
+        self.added
+            .into_iter()
+            .filter(move |val| self.removed.entry(*val).is_none())
+    }
+}
+
+#[test]
+fn check_laws() {
+    use crate::partially_verify_semilattice_laws;
+
+    let mut a = TwoPhaseSetLattice::default();
+    a.insert("alice");
+    a.insert("bob");
+
+    // One replica retracts an element, the other keeps it.
+    let mut b = a.clone();
+    b.remove("bob");
+    let mut c = a.clone();
+    c.insert("carol");
+
+    let d = b.clone().join(c.clone());
+    assert!(d.contains(&"alice"));
+    assert!(!d.contains(&"bob"));
+    assert!(d.contains(&"carol"));
+    assert_eq!(
+        d.iter().collect::<alloc::vec::Vec<_>>(),
+        [&"alice", &"carol"]
+    );
+
+    // Re-inserting does not resurrect the element.
+    let mut e = d.clone();
+    e.insert("bob");
+    assert!(!e.contains(&"bob"));
+
+    partially_verify_semilattice_laws([a, b, c, d]);
+}
@@ -8,7 +8,11 @@ use crate::{partial_ord_helper, Semilattice};
 use alloc::{vec, vec::Vec};
 
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 #[cfg_attr(
     feature = "minicbor",
     derive(minicbor::Encode, minicbor::Decode),
@@ -53,9 +57,12 @@ where
             Some(Ordering::Greater | Ordering::Equal) => self,
             Some(Ordering::Less) => other,
             None => {
-                for (l, r) in self.inner.iter_mut().zip(other.inner) {
+                let mut other = other.inner.into_iter();
+                for (l, r) in self.inner.iter_mut().zip(&mut other) {
                     l.join_assign(r);
                 }
+                // Keep the elements beyond the end of the shorter vec.
+                self.inner.extend(other);
 
                 self
             }
@@ -95,3 +102,28 @@ where
         self.inner.get_mut(key as usize).expect("BUG!")
     }
 }
+
+#[test]
+fn check_laws() {
+    use crate::{partially_verify_semilattice_laws, Max};
+
+    let a = VecLattice {
+        inner: vec![Max(1), Max(0), Max(3)],
+    };
+    let b = VecLattice {
+        inner: vec![Max(0), Max(2)],
+    };
+    let c = VecLattice {
+        inner: vec![Max(1)],
+    };
+
+    assert_eq!(
+        a.clone().join(b.clone()),
+        VecLattice {
+            inner: vec![Max(1), Max(2), Max(3)]
+        }
+    );
+    assert_eq!(b.clone().join(a.clone()), a.clone().join(b.clone()));
+
+    partially_verify_semilattice_laws([a, b, c, VecLattice::default()]);
+}
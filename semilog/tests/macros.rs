@@ -1,13 +1,210 @@
-use semilog::Semilattice;
+use core::marker::PhantomData;
 
-#[derive(Default, PartialEq, Semilattice)]
+#[cfg(feature = "alloc")]
+use semilog::SetLattice;
+use semilog::{partially_verify_semilattice_laws, Max, Semilattice};
+
+#[derive(Clone, Debug, Default, PartialEq, Semilattice)]
 struct PairR<A, B> {
     a: A,
     b: B,
 }
 
-#[derive(Default, PartialEq, Semilattice)]
+#[derive(Clone, Debug, Default, PartialEq, Semilattice)]
 struct PairT<A, B>(A, B);
 
-#[derive(Default, PartialEq, Semilattice)]
+#[derive(Clone, Debug, Default, PartialEq, Semilattice)]
 struct Singleton;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct NotALattice;
+
+#[derive(Clone, Debug, Default, PartialEq, Semilattice)]
+struct Cached {
+    value: Max<u8>,
+    #[semilattice(skip)]
+    cache: NotALattice,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Semilattice)]
+struct Tagged<T: Default + PartialOrd>(Max<u8>, #[semilattice(skip)] PhantomData<T>);
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Semilattice)]
+#[semilattice(bound = "T: Ord")]
+struct Labels<T>(SetLattice<T>);
+
+#[cfg(feature = "alloc")]
+impl<T> Default for Labels<T> {
+    fn default() -> Self {
+        Self(SetLattice::default())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Semilattice)]
+#[semilattice(identity)]
+struct Watermark {
+    seen: Max<u8>,
+    #[semilattice(skip)]
+    label: &'static str,
+}
+
+impl Default for Watermark {
+    fn default() -> Self {
+        Self {
+            seen: Max(5),
+            label: "default",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Semilattice)]
+#[semilattice(identity)]
+enum Status {
+    #[default]
+    Open,
+    Closed {
+        reason: Max<u8>,
+    },
+    Locked(Max<u8>, Max<u8>),
+}
+
+#[test]
+fn check_struct_laws() {
+    partially_verify_semilattice_laws([
+        PairR {
+            a: Max(0),
+            b: Max(1),
+        },
+        PairR {
+            a: Max(1),
+            b: Max(0),
+        },
+        PairR {
+            a: Max(1),
+            b: Max(1),
+        },
+    ]);
+
+    let mut pair = PairR {
+        a: Max(0),
+        b: Max(1),
+    };
+    pair.join_assign(PairR {
+        a: Max(1),
+        b: Max(0),
+    });
+    assert_eq!(
+        pair,
+        PairR {
+            a: Max(1),
+            b: Max(1)
+        }
+    );
+
+    partially_verify_semilattice_laws([PairT(Max(0), Max(1)), PairT(Max(1), Max(0))]);
+
+    partially_verify_semilattice_laws([Singleton]);
+}
+
+#[test]
+fn check_skipped_fields() {
+    let a = Cached {
+        value: Max(1),
+        cache: NotALattice,
+    };
+    let b = Cached {
+        value: Max(2),
+        cache: NotALattice,
+    };
+
+    assert_eq!(a.clone().join(b.clone()), b);
+
+    let mut c = a.clone();
+    c.join_assign(b.clone());
+    assert_eq!(c, b);
+    assert!(a < b);
+
+    partially_verify_semilattice_laws([a, b]);
+    partially_verify_semilattice_laws([
+        Tagged::<()>(Max(1), PhantomData),
+        Tagged(Max(2), PhantomData),
+    ]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn check_bound_override() {
+    let a = Labels(SetLattice::singleton("bug"));
+    let b = Labels(SetLattice::singleton("feature"));
+
+    assert_eq!(
+        a.clone().join(b.clone()),
+        Labels(SetLattice::from_iter(["bug", "feature"]))
+    );
+
+    partially_verify_semilattice_laws([a, b]);
+}
+
+#[test]
+fn check_identity() {
+    let bottom = Watermark::bottom();
+    assert_eq!(
+        bottom,
+        Watermark {
+            seen: Max(0),
+            label: "",
+        }
+    );
+
+    let a = Watermark {
+        seen: Max(3),
+        label: "a",
+    };
+    assert_eq!(a.clone().join(bottom), a);
+
+    assert_eq!(Status::bottom(), Status::Open);
+}
+
+#[test]
+fn check_enum_laws() {
+    use Status::{Closed, Locked, Open};
+
+    assert_eq!(
+        Open.join(Closed { reason: Max(1) }),
+        Closed { reason: Max(1) }
+    );
+    assert_eq!(
+        Closed { reason: Max(2) }.join(Closed { reason: Max(1) }),
+        Closed { reason: Max(2) }
+    );
+    assert_eq!(
+        Locked(Max(0), Max(1)).join(Closed { reason: Max(2) }),
+        Locked(Max(0), Max(1))
+    );
+    assert_eq!(
+        Locked(Max(0), Max(1)).join(Locked(Max(1), Max(0))),
+        Locked(Max(1), Max(1))
+    );
+    assert_eq!(
+        Locked(Max(0), Max(1)).partial_cmp(&Locked(Max(1), Max(0))),
+        None
+    );
+
+    let mut status = Closed { reason: Max(1) };
+    status.join_assign(Open);
+    assert_eq!(status, Closed { reason: Max(1) });
+    status.join_assign(Closed { reason: Max(2) });
+    assert_eq!(status, Closed { reason: Max(2) });
+    status.join_assign(Locked(Max(0), Max(0)));
+    assert_eq!(status, Locked(Max(0), Max(0)));
+
+    partially_verify_semilattice_laws([
+        Open,
+        Closed { reason: Max(0) },
+        Closed { reason: Max(1) },
+        Locked(Max(0), Max(1)),
+        Locked(Max(1), Max(0)),
+        Locked(Max(1), Max(1)),
+    ]);
+}
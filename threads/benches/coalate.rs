@@ -0,0 +1,35 @@
+//! Compare the serial and parallel coalation with
+//! `cargo bench --bench coalate` and
+//! `cargo bench --bench coalate --features parallel`.
+
+#![feature(test)]
+
+extern crate test;
+
+use std::{env, fs};
+
+use threads::{Actor, Root, THREADS_REF};
+
+#[bench]
+fn coalate_many_slices(b: &mut test::Bencher) {
+    let path = env::temp_dir().join(format!("threads-bench-coalate-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    let repo = git2::Repository::init_bare(&path).expect("Failed to create repository");
+
+    let mut root = Root::default();
+    for i in 0..1000 {
+        let name = format!("actor-{}", i);
+        let mut actor = Actor::new(root.inner.entry_mut(&name), name.clone(), 0);
+        let thread = actor
+            .new_thread(format!("Thread {}", i), "Hello".to_owned(), [])
+            .unwrap();
+        for j in 0..20 {
+            actor.reply(thread.clone(), format!("Reply {}", j)).unwrap();
+        }
+    }
+    root.save_all_to_git(&repo, THREADS_REF).unwrap();
+
+    b.iter(|| Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap());
+
+    fs::remove_dir_all(path).unwrap();
+}
@@ -0,0 +1,27 @@
+//! Render a wide thread with `cargo bench --bench display`.
+
+#![feature(test)]
+
+extern crate test;
+
+use threads::{detailed::Detailed, Actor, Root};
+
+#[bench]
+fn display_wide_thread(b: &mut test::Bencher) {
+    let mut root = Root::default();
+    let thread = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Wide".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    for i in 0..1000 {
+        bob.reply(thread.clone(), format!("Reply {}", i)).unwrap();
+    }
+
+    let detailed = Detailed::default().join_root(root);
+
+    let mut buffer = Vec::new();
+    b.iter(|| {
+        buffer.clear();
+        detailed.display_to(&mut buffer).unwrap();
+    });
+}
@@ -1,6 +1,6 @@
 use std::io::{self, BufRead, Read, Write};
 
-use threads::{detailed::Detailed, Actor, Root};
+use threads::{detailed::Detailed, Actor, Root, Tag, CACHE_REF, THREADS_REF};
 
 fn usage(code: i32) -> ! {
     print!(
@@ -13,6 +13,7 @@ USAGE:
 FLAGS:
   -h, --help            Prints help information
   -a, --actor           Who are you? {{Alice, Bob, Carol, Dave, Eve}}
+  -d, --device          Which of your devices? (default: 0)
 
 SUBCOMMANDS:
   list          List all threads
@@ -21,6 +22,7 @@ SUBCOMMANDS:
   edit          Edit your own message
   react         React to any message
   dump          Debug print the root object
+  stats         Summarize the size of the root object
 "
     );
 
@@ -35,6 +37,7 @@ fn main() -> Result<(), pico_args::Error> {
     }
 
     let actor_name: String = pargs.value_from_str(["-a", "--actor"])?;
+    let device = pargs.opt_value_from_str(["-d", "--device"])?.unwrap_or(0);
 
     let repo = git2::Repository::open_bare(format!("{}/actors/shared", env!("CARGO_MANIFEST_DIR")))
         .expect(
@@ -46,10 +49,14 @@ fn main() -> Result<(), pico_args::Error> {
 
     print!("Loading cache... ");
     std::io::stdout().flush().unwrap();
-    let mut root = Root::load_cache_from_git(&repo);
+    let mut root = Root::load_cache_from_git(&repo, CACHE_REF).expect("Failed to load cache");
     println!("done.");
 
-    let mut actor = Actor::new(root.inner.entry_mut(&actor_name), actor_name.to_owned());
+    let mut actor = Actor::new(
+        root.inner.entry_mut(&actor_name),
+        actor_name.to_owned(),
+        device,
+    );
 
     let input = io::stdin();
     let mut input = input.lock();
@@ -86,45 +93,58 @@ fn main() -> Result<(), pico_args::Error> {
             Detailed::default().join_root(root).display();
             return Ok(());
         }
+        "stats" => {
+            println!("{:#?}", root.stats());
+            return Ok(());
+        }
         "new" => {
-            actor.new_thread(
-                read_line("Title:", &mut input).trim().to_owned(),
-                read_to_string("Body:", &mut input).trim().to_owned(),
-                [],
-            );
+            actor
+                .new_thread(
+                    read_line("Title:", &mut input).trim().to_owned(),
+                    read_to_string("Body:", &mut input).trim().to_owned(),
+                    [],
+                )
+                .expect("Failed to start thread");
         }
         "reply" => {
-            actor.reply(
-                (
-                    read_line("Reply to who?", &mut input).trim().to_owned(),
+            actor
+                .reply(
+                    (
+                        read_line("Reply to who?", &mut input).trim().to_owned(),
+                        read_line("Message ID:", &mut input)
+                            .trim()
+                            .parse()
+                            .expect("Invalid number"),
+                    ),
+                    read_to_string("Body:", &mut input).trim().to_owned(),
+                )
+                .expect("Failed to reply");
+        }
+        "edit" => {
+            actor
+                .edit(
                     read_line("Message ID:", &mut input)
                         .trim()
                         .parse()
                         .expect("Invalid number"),
-                ),
-                read_to_string("Body:", &mut input).trim().to_owned(),
-            );
-        }
-        "edit" => {
-            actor.edit(
-                read_line("Message ID:", &mut input)
-                    .trim()
-                    .parse()
-                    .expect("Invalid number"),
-                read_to_string("Body:", &mut input).trim().to_owned(),
-            );
+                    read_to_string("Body:", &mut input).trim().to_owned(),
+                )
+                .expect("Failed to edit");
         }
         "redact" => {
-            actor.redact(
-                read_line("Message ID:", &mut input)
-                    .trim()
-                    .parse()
-                    .expect("Invalid number"),
-                read_line("Version:", &mut input)
-                    .trim()
-                    .parse()
-                    .expect("Invalid number"),
-            );
+            let id = read_line("Message ID:", &mut input)
+                .trim()
+                .parse()
+                .expect("Invalid number");
+            actor
+                .redact(
+                    (actor_name.clone(), id),
+                    read_line("Version:", &mut input)
+                        .trim()
+                        .parse()
+                        .expect("Invalid number"),
+                )
+                .expect("Failed to redact");
         }
         "react" => {
             let target_actor = read_line("Which actor authored the message?", &mut input)
@@ -144,7 +164,7 @@ fn main() -> Result<(), pico_args::Error> {
                 positive = false;
             }
 
-            actor.react((target_actor, message_id), reaction.to_owned(), positive);
+            actor.react((target_actor, message_id), reaction.into(), positive);
         }
         "tag" => {
             let message_id = (
@@ -158,12 +178,14 @@ fn main() -> Result<(), pico_args::Error> {
             );
 
             let line = read_line("Add comma separated tags:", &mut input);
-            let additive = line.trim().split(',').map(|x| x.trim().to_owned());
+            let additive = line.trim().split(',').map(|x| Tag::from(x.trim()));
 
             let line = read_line("Remove comma separated tags:", &mut input);
-            let negative = line.trim().split(',').map(|x| x.trim().to_owned());
+            let negative = line.trim().split(',').map(|x| Tag::from(x.trim()));
 
-            actor.adjust_tags(message_id, additive, negative);
+            actor
+                .adjust_tags(message_id, additive, negative)
+                .expect("Failed to adjust tags");
         }
         "import" => {
             panic!("GitHub issue import has been disabled. Edit the code to play with this.");
@@ -240,8 +262,10 @@ fn main() -> Result<(), pico_args::Error> {
         _ => usage(2),
     }
 
-    root.save_actor_slice_to_git(&repo, &actor_name);
-    root.save_cache_to_git(&repo);
+    root.save_actor_slice_to_git(&repo, THREADS_REF, &actor_name)
+        .expect("Failed to save actor slice");
+    root.save_cache_if_changed(&repo, CACHE_REF)
+        .expect("Failed to save cache");
 
     Ok(())
 }
@@ -1,12 +1,22 @@
 use core::ops;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
 
 use semilog::{MapLattice, Max, Redactable, Semilattice, SetLattice, VecLattice};
 
-use crate::{ActorID, MessageID, Owned, Patchset, Reaction, Root, Shared, Slice, Tag};
+use crate::{
+    find_reference, schema, ActorID, Attachment, ContentType, DisplayName, MessageID, Oid, Owned,
+    Patchset, Quote, Reaction, ReactionWeight, Redaction, Redirect, Root, Shared, Slice, Tag,
+    TagWeight, ThreadsError, DEFAULT_CONTENT_TYPE,
+};
 
 #[derive(Default, Debug, Clone, Semilattice, PartialEq, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 #[cbor(transparent)]
 pub struct Vote<const N: usize>(#[n(0)] MapLattice<ActorID, Max<u64>>);
 
@@ -24,8 +34,85 @@ impl<const N: usize> ops::DerefMut for Vote<N> {
     }
 }
 
+/// Votes on a tag: neutral, positive, or negative.
+pub type TagVote = Vote<4>;
+/// Votes on a reaction: absent or present.
+pub type ReactionVote = Vote<2>;
+
+impl Vote<4> {
+    pub const NEUTRAL: u64 = 0;
+    pub const POSITIVE: u64 = 1;
+    pub const NEGATIVE: u64 = 2;
+}
+
+impl Vote<2> {
+    pub const ABSENT: u64 = 0;
+    pub const PRESENT: u64 = 1;
+}
+
+/// The number of actors who made each choice of a `Vote<N>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tally<const N: usize>([usize; N]);
+
+impl<const N: usize> Tally<N> {
+    /// The number of actors whose vote is `choice`.
+    pub fn get(&self, choice: u64) -> usize {
+        self.0[choice as usize]
+    }
+}
+
+impl Tally<4> {
+    pub fn neutral(&self) -> usize {
+        self.get(TagVote::NEUTRAL)
+    }
+
+    pub fn positive(&self) -> usize {
+        self.get(TagVote::POSITIVE)
+    }
+
+    pub fn negative(&self) -> usize {
+        self.get(TagVote::NEGATIVE)
+    }
+
+    /// Positive less negative votes.
+    pub fn score(&self) -> i64 {
+        self.positive() as i64 - self.negative() as i64
+    }
+}
+
+impl Tally<2> {
+    pub fn absent(&self) -> usize {
+        self.get(ReactionVote::ABSENT)
+    }
+
+    pub fn present(&self) -> usize {
+        self.get(ReactionVote::PRESENT)
+    }
+}
+
 impl<const N: usize> Vote<N> {
-    pub fn aggregate(&self) -> [usize; N] {
+    /// The greatest count which can be advanced to any choice without
+    /// overflowing.
+    pub const MAX_COUNT: u64 = u64::MAX - (N as u64 - 1);
+
+    /// Each actor's vote is a monotonic counter whose choice is the count
+    /// modulo `N`. Advance `vote` to the least count with the given choice,
+    /// which is a no-op if the vote already has that choice. A count beyond
+    /// `MAX_COUNT` saturates rather than overflowing, so may keep its choice.
+    pub fn advance(vote: &mut Max<u64>, choice: u64) {
+        let n = N as u64;
+        assert!(choice < n, "Vote choice out of range");
+
+        vote.0 = vote.0.saturating_add((choice + n - vote.0 % n) % n);
+    }
+
+    /// Record `actor`'s choice, which must be in `[0, N)`.
+    pub fn set(&mut self, actor: &str, choice: u64) {
+        Self::advance(self.0.entry_mut(actor), choice);
+    }
+
+    /// The number of actors who made each choice.
+    pub fn aggregate(&self) -> Tally<N> {
         let mut res = [0; N];
 
         for (_, v) in &self.inner {
@@ -36,16 +123,17 @@ impl<const N: usize> Vote<N> {
             res[v.0 as usize % N] += 1;
         }
 
-        res
+        Tally(res)
     }
 }
 
 #[derive(Default, Debug, Clone, Semilattice, PartialEq, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Comment {
     #[n(0)]
     titles: VecLattice<SetLattice<String>>,
     #[n(1)]
-    content: VecLattice<Redactable<String>>,
+    content: MapLattice<u64, Redactable<String>>,
     #[n(2)]
     responses: SetLattice<MessageID>,
     #[n(3)]
@@ -54,125 +142,2052 @@ struct Comment {
     reactions: MapLattice<Reaction, Vote<2>>,
     #[n(5)]
     commits: VecLattice<SetLattice<Patchset>>,
+    #[n(6)]
+    hidden: Option<Max<u64>>,
+    #[n(7)]
+    tag_weights: MapLattice<Tag, MapLattice<ActorID, TagWeight>>,
+    #[n(8)]
+    pinned: Option<Max<u64>>,
+    #[n(9)]
+    created_at: Option<Max<u64>>,
+    #[n(10)]
+    redaction_reasons: Option<MapLattice<u64, SetLattice<String>>>,
+    #[n(11)]
+    attachments: Option<MapLattice<u64, Redactable<Attachment>>>,
+    #[n(12)]
+    content_types: Option<MapLattice<u64, ContentType>>,
+    #[n(13)]
+    merged_into: Option<Redirect>,
+    /// Every thread which was ever merged into this one, including those
+    /// which have since been merged elsewhere.
+    #[n(14)]
+    merged_from: Option<SetLattice<MessageID>>,
+    #[n(15)]
+    quotes: Option<SetLattice<Quote>>,
+    #[n(16)]
+    reaction_weights: Option<MapLattice<Reaction, MapLattice<ActorID, ReactionWeight>>>,
+    #[n(17)]
+    redactions: Option<MapLattice<u64, SetLattice<Redaction>>>,
+    #[n(18)]
+    edited_at: Option<Max<u64>>,
+}
+
+impl Comment {
+    /// The latest version of the content, or `None` if it was redacted.
+    /// Concurrent edits share the same version number in the high bits of
+    /// their keys, so the edit from the device with the greatest ID wins.
+    pub fn current_content(&self) -> Option<&str> {
+        let (_, content) = self.content.last_key_value()?;
+        content.as_data().map(String::as_str)
+    }
+
+    /// Every version of the content in order, including redacted versions.
+    pub fn history(&self) -> impl Iterator<Item = (u64, &Redactable<String>)> {
+        self.content
+            .iter()
+            .map(|(version, content)| (*version, content))
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.hidden.is_some_and(|vote| vote.0 % 2 == 1)
+    }
+
+    /// The score of each tag, positive less negative votes, by tag.
+    fn tag_scores(&self) -> impl Iterator<Item = (&Tag, i64)> {
+        self.tags
+            .iter()
+            .map(|(tag, votes)| (tag, votes.aggregate().score()))
+    }
 }
 
 #[derive(Default, Debug, Clone, Semilattice, PartialEq, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Detailed {
     #[n(0)]
     threads: SetLattice<MessageID>,
     #[n(1)]
-    comments: MapLattice<ActorID, VecLattice<Comment>>,
+    comments: MapLattice<ActorID, MapLattice<u64, Comment>>,
+    /// The latest blob of each actor's slice which has been joined, as recorded
+    /// by `Root::materialize_incremental`.
+    #[n(2)]
+    #[semilattice(skip)]
+    materialized: BTreeMap<ActorID, Oid>,
+    #[n(3)]
+    names: MapLattice<ActorID, DisplayName>,
 }
 
 impl Detailed {
+    pub(crate) fn is_materialized(&self, actor: &str, oid: &[u8]) -> bool {
+        self.materialized.get(actor).map(Vec::as_slice) == Some(oid)
+    }
+
+    pub(crate) fn mark_materialized(&mut self, actor: ActorID, oid: Oid) {
+        self.materialized.insert(actor, oid);
+    }
+
     pub fn join_root(mut self, other: Root) -> Self {
-        for (actor, Slice { owned, shared }) in other.inner.inner {
-            for (
-                id,
-                Owned {
+        for (actor, slice) in other.inner {
+            self.join_slice(actor, slice);
+        }
+
+        self
+    }
+
+    /// Join a single actor's slice, as `join_root` does for each slice of a
+    /// root, e.g. after the actor wrote to it. Joining each slice of a root
+    /// in turn is the same as joining the root.
+    pub fn join_slice(&mut self, actor: ActorID, slice: Slice) {
+        let Slice {
+            owned,
+            shared,
+            display_name,
+        } = slice;
+
+        if let Some(name) = display_name {
+            self.names.entry_mut(&actor).join_assign(name);
+        }
+
+        for (
+            id,
+            Owned {
+                titles,
+                content,
+                commits,
+                hidden,
+                created_at,
+                redaction_reasons,
+                attachments,
+                content_types,
+                quotes,
+                redactions,
+                edited_at,
+            },
+        ) in owned
+        {
+            if !titles.is_empty() {
+                self.threads.insert((actor.clone(), id));
+            }
+
+            self.comments
+                .entry_mut(&actor)
+                .entry_mut(&id)
+                .join_assign(Comment {
                     titles,
                     content,
+                    reactions: MapLattice::default(),
+                    responses: SetLattice::default(),
+                    tags: MapLattice::default(),
                     commits,
+                    hidden,
+                    tag_weights: MapLattice::default(),
+                    pinned: None,
+                    created_at,
+                    redaction_reasons,
+                    attachments,
+                    content_types,
+                    merged_into: None,
+                    merged_from: None,
+                    quotes,
+                    reaction_weights: None,
+                    redactions,
+                    edited_at,
+                });
+        }
+
+        // Shared state is written by `actor` about messages authored by `aid`,
+        // so it is stored under the message but attributed to `actor`.
+        for (aid, comments) in shared {
+            for (
+                id,
+                Shared {
+                    tags,
+                    reactions,
+                    responses,
+                    tag_weights,
+                    pinned,
+                    merged_into,
+                    reaction_weights,
                 },
-            ) in owned.inner.into_iter().enumerate()
+            ) in comments
             {
-                let id = id as u64;
-                if !titles.is_empty() {
-                    self.threads.insert((actor.clone(), id));
+                if let Some(redirect) = &merged_into {
+                    self.comments
+                        .entry_mut(&redirect.value.0)
+                        .entry_mut(&redirect.value.1)
+                        .merged_from
+                        .get_or_insert_with(SetLattice::default)
+                        .insert((aid.clone(), id));
                 }
 
                 self.comments
-                    .entry_mut(&actor)
-                    .entry_mut(id)
+                    .entry_mut(&aid)
+                    .entry_mut(&id)
                     .join_assign(Comment {
-                        titles,
-                        content,
-                        reactions: MapLattice::default(),
-                        responses: SetLattice::default(),
-                        tags: MapLattice::default(),
-                        commits,
+                        reactions: MapLattice::from_iter(reactions.iter().map(|(r, v)| {
+                            (r.clone(), Vote(MapLattice::singleton(actor.clone(), *v)))
+                        })),
+                        tags: MapLattice::from_iter(tags.iter().map(|(r, v)| {
+                            (r.clone(), Vote(MapLattice::singleton(actor.clone(), *v)))
+                        })),
+                        responses: SetLattice::from_iter(
+                            responses.iter().map(|(id, _)| (actor.clone(), *id)),
+                        ),
+                        tag_weights: MapLattice::from_iter(tag_weights.into_iter().flatten().map(
+                            |(tag, weight)| (tag, MapLattice::singleton(actor.clone(), weight)),
+                        )),
+                        pinned,
+                        merged_into,
+                        reaction_weights: reaction_weights.map(|weights| {
+                            MapLattice::from_iter(weights.into_iter().map(|(reaction, weight)| {
+                                (reaction, MapLattice::singleton(actor.clone(), weight))
+                            }))
+                        }),
+                        ..Default::default()
                     });
             }
+        }
+    }
+}
 
-            for (aid, comments) in shared.inner {
-                for (
-                    id,
-                    Shared {
-                        tags,
-                        reactions,
-                        responses,
-                    },
-                ) in comments.inner
-                {
-                    self.comments
-                        .entry_mut(&aid)
-                        .entry_mut(id)
-                        .join_assign(Comment {
-                            reactions: MapLattice::from_iter(reactions.iter().map(|(r, v)| {
-                                (r.clone(), Vote(MapLattice::singleton(actor.clone(), *v)))
-                            })),
-                            tags: MapLattice::from_iter(tags.iter().map(|(r, v)| {
-                                (r.clone(), Vote(MapLattice::singleton(actor.clone(), *v)))
-                            })),
-                            responses: SetLattice::from_iter(
-                                responses.iter().map(|id| (actor.clone(), id.0)),
-                            ),
-                            ..Default::default()
-                        });
+/// A message and its replies, as returned by `Detailed::thread_tree`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadNode {
+    pub id: MessageID,
+    /// The display name of the author, if they have set one.
+    pub author_name: Option<String>,
+    /// Each version of the message, including those which were redacted.
+    pub content: Vec<(u64, Redactable<String>)>,
+    /// Replies, ordered by message ID.
+    pub children: Vec<ThreadNode>,
+}
+
+/// Statistics of a thread, as returned by `Detailed::thread_stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadStats {
+    /// The number of visible replies, however deeply nested.
+    pub reply_count: usize,
+    /// The latest activity of any visible message of the thread.
+    pub last_activity: Activity,
+}
+
+/// The latest change to a message, being its creation or an edit. Ordered by
+/// time, where any recorded time is later than none, then by the version of
+/// the content, and then by message ID.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Activity {
+    /// When the message was created or last edited, if recorded.
+    pub time: Option<u64>,
+    /// The latest version of the message's content.
+    pub version: u64,
+    pub message: MessageID,
+}
+
+/// The order of `Detailed::threads_page`. Ties are broken by thread ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadSort {
+    /// By thread ID, ascending.
+    Id,
+    /// The thread with the latest `Activity` first, by its time and then its
+    /// version.
+    LatestActivity,
+    /// The thread with the greatest total score of its tags first.
+    TagScore,
+}
+
+/// The title of a thread, as returned by `Detailed::thread_title`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TitleState<'a> {
+    Resolved(&'a str),
+    /// The thread was renamed concurrently, so a rename should be prompted
+    /// for. Ordered alphabetically.
+    Conflict(Vec<&'a str>),
+}
+
+/// A thread, as listed by `Detailed::threads_page`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadSummary {
+    pub id: MessageID,
+    /// The latest titles, as per `Detailed::titles`.
+    pub titles: Vec<String>,
+    /// The number of visible replies, however deeply nested.
+    pub reply_count: usize,
+    /// The tags with a positive score, greatest first.
+    pub top_tags: Vec<(Tag, i64)>,
+}
+
+impl Detailed {
+    /// The visible messages of the thread rooted at `root`, including itself,
+    /// each once, even if it replies to several of them or a reply closes a
+    /// cycle.
+    fn thread_messages<'a>(&'a self, root: &'a MessageID) -> Vec<(&'a MessageID, &'a Comment)> {
+        let mut messages = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![root];
+
+        while let Some(id) = stack.pop() {
+            let comment = match self.comment(id) {
+                Some(comment) if !comment.content.is_empty() && !comment.is_hidden() => comment,
+                _ => continue,
+            };
+            if visited.insert(id) {
+                stack.extend(self.replies(id, comment));
+                messages.push((id, comment));
+            }
+        }
+
+        messages
+    }
+
+    /// The statistics of the visible thread rooted at `root`, or `None` if
+    /// it is unknown or hidden.
+    pub fn thread_stats(&self, root: &MessageID) -> Option<ThreadStats> {
+        let messages = self.thread_messages(root);
+        if messages.is_empty() {
+            return None;
+        }
+
+        let (time, version, message) = messages
+            .iter()
+            .filter_map(|(id, comment)| {
+                let (version, _) = comment.content.last_key_value()?;
+                let time = comment.created_at.join(comment.edited_at);
+                Some((time.map(|Max(time)| time), *version, *id))
+            })
+            .max()?;
+
+        Some(ThreadStats {
+            reply_count: messages.len() - 1,
+            last_activity: Activity {
+                time,
+                version,
+                message: message.clone(),
+            },
+        })
+    }
+
+    /// The visible threads in which `actor` wrote a visible message, be it the
+    /// thread itself or a reply, ordered by ID. Replies which are not yet
+    /// connected to a materialized thread are not counted.
+    pub fn threads_by_actor(&self, actor: &str) -> Vec<MessageID> {
+        self.threads
+            .keys()
+            .filter(|id| !self.is_merged(id))
+            .filter(|id| {
+                self.thread_messages(id)
+                    .iter()
+                    .any(|(message, _)| message.0 == actor)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// A window of `limit` visible threads, after skipping `offset` of them,
+    /// in the given order.
+    pub fn threads_page(
+        &self,
+        offset: usize,
+        limit: usize,
+        sort: ThreadSort,
+    ) -> Vec<ThreadSummary> {
+        let mut threads: Vec<_> = self
+            .threads
+            .keys()
+            .filter(|id| !self.is_merged(id))
+            .filter_map(|id| {
+                let stats = self.thread_stats(id)?;
+                let comment = self.comment(id)?;
+
+                let mut top_tags: Vec<_> = comment
+                    .tag_scores()
+                    .filter(|(_, score)| *score > 0)
+                    .map(|(tag, score)| (tag.clone(), score))
+                    .collect();
+                top_tags.sort_by(|(t1, s1), (t2, s2)| s2.cmp(s1).then(t1.cmp(t2)));
+
+                let summary = ThreadSummary {
+                    id: id.clone(),
+                    titles: self.titles(id).into_iter().map(str::to_owned).collect(),
+                    reply_count: stats.reply_count,
+                    top_tags,
+                };
+                let Activity { time, version, .. } = stats.last_activity;
+                Some(((time, version), summary))
+            })
+            .collect();
+
+        // Threads are already ordered by ID, and the sort is stable.
+        match sort {
+            ThreadSort::Id => (),
+            ThreadSort::LatestActivity => threads.sort_by(|(a, _), (b, _)| b.cmp(a)),
+            ThreadSort::TagScore => threads.sort_by_key(|(_, summary)| {
+                -summary.top_tags.iter().map(|(_, score)| score).sum::<i64>()
+            }),
+        }
+
+        threads
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(_, summary)| summary)
+            .collect()
+    }
+
+    /// The latest version of a message's content, as per
+    /// `Comment::current_content`.
+    pub fn current_content(&self, id: &MessageID) -> Option<&str> {
+        self.comment(id)?.current_content()
+    }
+
+    /// The edit history of a message, as per `Comment::history`.
+    pub fn message_history(
+        &self,
+        id: &MessageID,
+    ) -> impl Iterator<Item = (u64, &Redactable<String>)> {
+        self.comment(id).into_iter().flat_map(Comment::history)
+    }
+
+    /// The content type of a version of a message's content.
+    pub fn content_type(&self, id: &MessageID, version: u64) -> &str {
+        self.comment(id)
+            .and_then(|comment| comment.content_types.as_ref()?.get(&version))
+            .map_or(DEFAULT_CONTENT_TYPE, |content_type| &content_type.value)
+    }
+
+    /// The attachments of a message which have not been redacted, with their
+    /// keys.
+    pub fn attachments(&self, id: &MessageID) -> Vec<(u64, &Attachment)> {
+        self.comment(id)
+            .and_then(|comment| comment.attachments.as_ref())
+            .into_iter()
+            .flat_map(|attachments| attachments.iter())
+            .filter_map(|(key, attachment)| Some((*key, attachment.as_data()?)))
+            .collect()
+    }
+
+    /// The spans of other messages which a message quotes, ordered by the
+    /// quoted message.
+    pub fn quotes(&self, id: &MessageID) -> Vec<&Quote> {
+        self.comment(id)
+            .and_then(|comment| comment.quotes.as_ref())
+            .map_or_else(Vec::new, |quotes| quotes.keys().collect())
+    }
+
+    /// Why a version of a message's content was redacted, if reasons were
+    /// given.
+    pub fn redaction_reasons(&self, id: &MessageID, version: u64) -> Vec<&str> {
+        self.comment(id)
+            .and_then(|comment| comment.redaction_reasons.as_ref()?.get(&version))
+            .into_iter()
+            .flat_map(|reasons| reasons.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// Who redacted a version of a message's content and when, if recorded,
+    /// ordered by who.
+    pub fn redactions(&self, id: &MessageID, version: u64) -> Vec<&Redaction> {
+        self.comment(id)
+            .and_then(|comment| comment.redactions.as_ref()?.get(&version))
+            .map_or_else(Vec::new, |redactions| redactions.keys().collect())
+    }
+
+    /// Every message with a version of its content containing `query`,
+    /// ignoring case, in order of message ID. Redacted versions never match.
+    pub fn search(&self, query: &str) -> Vec<MessageID> {
+        let query = query.to_lowercase();
+
+        self.comments
+            .iter()
+            .flat_map(|(actor, comments)| comments.iter().map(move |(id, c)| (actor, id, c)))
+            .filter(|(_, _, comment)| {
+                comment.content.values().any(|content| {
+                    content
+                        .as_data()
+                        .is_some_and(|text| text.to_lowercase().contains(&query))
+                })
+            })
+            .map(|(actor, id, _)| (actor.clone(), *id))
+            .collect()
+    }
+
+    /// The latest titles of a thread, of which there are several if it was
+    /// renamed concurrently. Empty if the message is not a thread.
+    pub fn titles(&self, id: &MessageID) -> Vec<&str> {
+        self.comment(id)
+            .and_then(|comment| comment.titles.last())
+            .map_or_else(Vec::new, |titles| {
+                titles.keys().map(String::as_str).collect()
+            })
+    }
+
+    /// Whether the thread has a single latest title, as per `titles`, or
+    /// `None` if the message is not a thread.
+    pub fn thread_title(&self, id: &MessageID) -> Option<TitleState<'_>> {
+        let mut titles = self.titles(id);
+        match titles.len() {
+            0 => None,
+            1 => titles.pop().map(TitleState::Resolved),
+            _ => Some(TitleState::Conflict(titles)),
+        }
+    }
+
+    /// When a message was created, as asserted by its author, if known.
+    pub fn created_at(&self, id: &MessageID) -> Option<u64> {
+        self.comment(id)?.created_at.map(|Max(time)| time)
+    }
+
+    /// Every thread, ordered by when it was created and then by ID. Threads
+    /// without a creation time come first.
+    pub fn threads_by_time(&self) -> Vec<MessageID> {
+        let mut threads: Vec<_> = self
+            .threads
+            .keys()
+            .filter(|id| !self.is_merged(id))
+            .map(|id| (self.created_at(id), id.clone()))
+            .collect();
+        threads.sort_unstable();
+
+        threads.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// The name which `actor` has chosen to be displayed as, if any.
+    pub fn display_name(&self, actor: &str) -> Option<&str> {
+        self.names.get(actor).map(|name| name.value.as_str())
+    }
+
+    fn comment(&self, id: &MessageID) -> Option<&Comment> {
+        self.comments.get(&id.0)?.get(&id.1)
+    }
+
+    /// The replies to a message, ordered by message ID, including the threads
+    /// which are currently merged into it.
+    fn replies<'a>(&'a self, id: &'a MessageID, comment: &'a Comment) -> Vec<&'a MessageID> {
+        let merged = comment
+            .merged_from
+            .iter()
+            .flat_map(|sources| sources.keys())
+            .filter(|source| self.merged_into(source) == Some(id));
+
+        let mut replies: Vec<_> = comment.responses.keys().chain(merged).collect();
+        replies.sort_unstable();
+        replies.dedup();
+        replies
+    }
+
+    /// The thread into which a thread was last merged, unless that has not
+    /// been materialized.
+    fn merged_into(&self, id: &MessageID) -> Option<&MessageID> {
+        let redirect = self.comment(id)?.merged_into.as_ref()?;
+        Some(&redirect.value).filter(|target| self.contains(target))
+    }
+
+    /// The thread into which a thread was merged, following merges of that
+    /// thread in turn, or the thread itself if it was not merged. Of threads
+    /// which were merged into each other, the least is canonical.
+    pub fn canonical_thread<'a>(&'a self, id: &'a MessageID) -> &'a MessageID {
+        let mut path = vec![id];
+
+        while let Some(target) = self.merged_into(path[path.len() - 1]) {
+            if let Some(start) = path.iter().position(|x| *x == target) {
+                return path[start..].iter().min().unwrap_or(&target);
+            }
+            path.push(target);
+        }
+
+        path[path.len() - 1]
+    }
+
+    fn is_merged(&self, thread: &MessageID) -> bool {
+        self.canonical_thread(thread) != thread
+    }
+
+    /// Whether the message has been materialized.
+    pub fn contains(&self, id: &MessageID) -> bool {
+        self.comment(id)
+            .is_some_and(|comment| !comment.content.is_empty())
+    }
+
+    /// Messages which are referenced, e.g. replied or reacted to, but which
+    /// have not been materialized, as happens when only some slices have been
+    /// joined. Sorted by message ID.
+    pub fn dangling_refs(&self) -> Vec<MessageID> {
+        let mut dangling = Vec::new();
+
+        for (actor, comments) in &self.comments {
+            for (id, comment) in comments {
+                if comment.content.is_empty() {
+                    dangling.push((actor.clone(), *id));
+                }
+
+                for child in comment.responses.keys() {
+                    if !self.contains(child) {
+                        dangling.push(child.clone());
+                    }
                 }
             }
         }
 
-        self
+        dangling.sort_unstable();
+        dangling.dedup();
+        dangling
+    }
+
+    /// The pinned messages in the thread rooted at `thread`, including itself,
+    /// in the order in which they were pinned.
+    pub fn pinned_messages(&self, thread: &MessageID) -> Vec<MessageID> {
+        let mut pinned = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![thread];
+
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+
+            if let Some(comment) = self.comment(id) {
+                match comment.pinned {
+                    Some(Max(clock)) if clock % 2 == 1 => pinned.push((clock, id.clone())),
+                    _ => (),
+                }
+                stack.extend(self.replies(id, comment));
+            }
+        }
+
+        pinned.sort_unstable();
+        pinned.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// The weight of each tag on a message, being the greatest of the weights
+    /// set by each actor.
+    pub fn tag_weights(&self, id: &MessageID) -> BTreeMap<&Tag, u8> {
+        self.comment(id)
+            .into_iter()
+            .flat_map(|comment| comment.tag_weights.iter())
+            .filter_map(|(tag, weights)| {
+                let weight = weights.values().map(|weight| weight.value.0).max()?;
+                Some((tag, weight))
+            })
+            .collect()
+    }
+
+    /// The total weight of each weighted reaction to a message, summed over
+    /// every actor's latest weight. Reactions without weight are omitted.
+    pub fn reaction_weights(&self, id: &MessageID) -> BTreeMap<&Reaction, u64> {
+        self.comment(id)
+            .and_then(|comment| comment.reaction_weights.as_ref())
+            .into_iter()
+            .flat_map(|reactions| reactions.iter())
+            .map(|(reaction, weights)| {
+                let total = weights
+                    .values()
+                    .fold(0, |sum: u64, weight| sum.saturating_add(weight.value.0));
+                (reaction, total)
+            })
+            .filter(|(_, total)| *total > 0)
+            .collect()
+    }
+
+    /// The tree of replies rooted at `root`, or `None` if the message is
+    /// unknown or hidden. Replies which have not been materialized yet, or
+    /// which are hidden, are omitted, as are replies which would close a
+    /// cycle.
+    pub fn thread_tree(&self, root: &MessageID) -> Option<ThreadNode> {
+        self.subtree(root, &mut Vec::new())
+    }
+
+    fn subtree<'a>(
+        &'a self,
+        id: &'a MessageID,
+        ancestors: &mut Vec<&'a MessageID>,
+    ) -> Option<ThreadNode> {
+        if ancestors.contains(&id) {
+            return None;
+        }
+        let comment = self.comment(id).filter(|comment| !comment.is_hidden())?;
+
+        ancestors.push(id);
+        let children = self
+            .replies(id, comment)
+            .into_iter()
+            .filter_map(|child| self.subtree(child, ancestors))
+            .collect();
+        ancestors.pop();
+
+        Some(ThreadNode {
+            id: id.clone(),
+            author_name: self.display_name(&id.0).map(str::to_owned),
+            content: comment.content.to_vec(),
+            children,
+        })
+    }
+
+    /// Cycles of replies, which only a buggy or malicious actor creates. Each
+    /// cycle starts from its least message ID.
+    pub fn cycles(&self) -> Vec<Vec<MessageID>> {
+        let mut cycles = Vec::new();
+        let mut path = Vec::new();
+        let mut done = BTreeSet::new();
+
+        // Every message in a cycle is a reply to another.
+        for (_, comments) in &self.comments {
+            for (_, comment) in comments {
+                for id in comment.responses.keys() {
+                    self.find_cycles(id, &mut path, &mut done, &mut cycles);
+                }
+            }
+        }
+
+        cycles.sort_unstable();
+        cycles.dedup();
+        cycles
+    }
+
+    fn find_cycles<'a>(
+        &'a self,
+        id: &'a MessageID,
+        path: &mut Vec<&'a MessageID>,
+        done: &mut BTreeSet<&'a MessageID>,
+        cycles: &mut Vec<Vec<MessageID>>,
+    ) {
+        if let Some(start) = path.iter().position(|x| *x == id) {
+            let mut cycle = path[start..]
+                .iter()
+                .map(|x| (*x).clone())
+                .collect::<Vec<_>>();
+            let least = (0..cycle.len()).min_by_key(|i| &cycle[*i]).unwrap_or(0);
+            cycle.rotate_left(least);
+            cycles.push(cycle);
+            return;
+        }
+        if !done.insert(id) {
+            return;
+        }
+
+        path.push(id);
+        for child in self
+            .comment(id)
+            .into_iter()
+            .flat_map(|c| c.responses.keys())
+        {
+            self.find_cycles(child, path, done, cycles);
+        }
+        path.pop();
+    }
+}
+
+impl Detailed {
+    /// Load the view saved by `save_to_git`, or an empty view if the reference
+    /// does not exist.
+    pub fn load_from_git(repo: &git2::Repository, refname: &str) -> Result<Self, ThreadsError> {
+        match find_reference(repo, refname)? {
+            Some(r) => schema::decode(r.peel_to_blob()?.content()),
+            None => Ok(Detailed::default()),
+        }
+    }
+
+    /// Save the view, including which blobs it has materialized, such that it
+    /// need not be joined again from every slice.
+    pub fn save_to_git(&self, repo: &git2::Repository, refname: &str) -> Result<(), ThreadsError> {
+        let mut buffer = Vec::new();
+
+        schema::encode(self, &mut buffer)?;
+
+        repo.reference(refname, repo.blob(&buffer)?, true, "log msg")?;
+
+        Ok(())
     }
 }
 
 impl Detailed {
     // An awful example UI.
     pub fn display(&self) {
+        self.display_to(&mut io::stdout())
+            .expect("Failed to write to stdout");
+    }
+
+    /// Write the output of `display` to `w`. Threads and the replies to each
+    /// message are written depth-first in order of message ID, so the output
+    /// depends only on the state and not on the order in which it was joined.
+    /// Hidden or unmaterialized messages and their replies are omitted.
+    pub fn display_to(&self, w: &mut impl io::Write) -> io::Result<()> {
+        let empty = MapLattice::default();
+        let missing = Comment::default();
         let mut stack = Vec::new();
+        let mut path = Vec::new();
 
         for (mid, _) in &**self.threads {
+            if self.comment(mid).is_some_and(Comment::is_hidden) || self.is_merged(mid) {
+                continue;
+            }
+
             stack.clear();
             stack.push((0, mid));
 
             while let Some((depth, id)) = stack.pop() {
-                let comment = self
-                    .comments
-                    .entry(&id.0)
-                    .expect("Expected aid")
-                    .entry(id.1)
-                    .expect("Expected id.");
-
-                stack.extend(comment.responses.into_iter().map(|x| (depth + 1, x)));
-
-                println!("Depth: {}", depth);
-                println!("Author: {:?} [{}]", id.0, id.1);
-
-                let mut tag_votes = BTreeMap::new();
-                for (tag, votes) in &*comment.tags {
-                    let va = votes.aggregate();
-                    *tag_votes.entry(tag).or_insert(0) += va[1] as i64 - va[2] as i64;
+                // `path` holds the ancestors of `id`; a reply to any of them
+                // would close a cycle, see `cycles`.
+                path.truncate(depth);
+                if path.contains(&id) {
+                    continue;
+                }
+
+                // A message which has not been materialized is skipped along
+                // with its replies, see `dangling_refs`.
+                let comment = self.comments.get_or(&id.0, &empty).get_or(&id.1, &missing);
+                if comment.content.is_empty() || comment.is_hidden() {
+                    continue;
+                }
+
+                // Responses are sorted by message ID; push them in reverse so
+                // that they are popped in order.
+                let start = stack.len();
+                stack.extend(
+                    self.replies(id, comment)
+                        .into_iter()
+                        .map(|x| (depth + 1, x)),
+                );
+                stack[start..].reverse();
+                path.push(id);
+
+                writeln!(w, "Depth: {}", depth)?;
+                match self.display_name(&id.0) {
+                    Some(name) => writeln!(w, "Author: {} ({:?}) [{}]", name, id.0, id.1)?,
+                    None => writeln!(w, "Author: {:?} [{}]", id.0, id.1)?,
                 }
 
-                print!("Tags: ");
-                for (tag, score) in tag_votes.into_iter().filter(|(_, x)| *x > 0) {
-                    print!("{}, ({}), ", tag, score);
+                write!(w, "Tags: ")?;
+                for (tag, score) in comment.tag_scores().filter(|(_, x)| *x > 0) {
+                    write!(w, "{}, ({}), ", tag, score)?;
                 }
-                println!();
+                writeln!(w)?;
 
-                for (version, content) in comment.content.iter().enumerate() {
-                    println!("Body [{}]: {:?}", version, content);
+                for (version, content) in &comment.content {
+                    writeln!(w, "Body [{}]: {:?}", version, content)?;
                 }
-                print!("Reactions: ");
+                write!(w, "Reactions: ")?;
                 for (reaction, votes) in &*comment.reactions {
-                    print!("{} ({:?})", reaction, votes);
+                    write!(w, "{} ({:?})", reaction, votes)?;
                 }
-                println!();
-                println!();
+                writeln!(w)?;
+                writeln!(w)?;
             }
 
-            println!("---");
+            writeln!(w, "---")?;
         }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn reactions_are_idempotent() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let id = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Body".to_owned(), [])
+        .unwrap();
+
+    let aggregate = |root: &Root| {
+        Detailed::default()
+            .join_root(root.clone())
+            .comments
+            .entry("alice")
+            .and_then(|comments| comments.entry(&id.1))
+            .and_then(|comment| comment.reactions.entry(":+1:"))
+            .map(|votes| {
+                let tally = votes.aggregate();
+                (tally.absent(), tally.present())
+            })
+    };
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.react(id.clone(), ":+1:".into(), true);
+    bob.react(id.clone(), ":+1:".into(), true);
+    assert_eq!(aggregate(&root), Some((0, 1)));
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.react(id.clone(), ":+1:".into(), false);
+    bob.react(id.clone(), ":+1:".into(), false);
+    assert_eq!(aggregate(&root), Some((1, 0)));
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.react(id.clone(), ":+1:".into(), true);
+    assert_eq!(aggregate(&root), Some((0, 1)));
+
+    // Un-reacting without having reacted records an absent vote.
+    let mut carol = Actor::new(root.inner.entry_mut("carol"), "carol".to_owned(), 0);
+    carol.react(id.clone(), ":+1:".into(), false);
+    assert_eq!(aggregate(&root), Some((1, 1)));
+}
+
+#[test]
+fn vote_transitions() {
+    // From each choice, setting each choice yields that choice.
+    for from in 0..4 {
+        for to in 0..4 {
+            let mut vote = TagVote::default();
+            vote.set("alice", from);
+            let before = *vote.entry("alice").unwrap();
+
+            vote.set("alice", to);
+            let after = *vote.entry("alice").unwrap();
+            assert_eq!(after.0 % 4, to);
+            assert!(after >= before);
+            assert_eq!(after == before, from == to);
+        }
+    }
+
+    let mut vote = TagVote::default();
+    vote.set("alice", TagVote::POSITIVE);
+    vote.set("bob", TagVote::NEGATIVE);
+    vote.set("carol", TagVote::POSITIVE);
+    vote.set("carol", TagVote::NEUTRAL);
+    let tally = vote.aggregate();
+    assert_eq!(
+        (tally.neutral(), tally.positive(), tally.negative()),
+        (1, 1, 1)
+    );
+    assert_eq!(tally.score(), 0);
+
+    // Of concurrent choices, the one with the greater count wins.
+    let mut a = vote.clone();
+    let mut b = vote;
+    a.set("alice", TagVote::NEGATIVE);
+    b.set("alice", TagVote::NEUTRAL);
+    assert_eq!(a.clone().join(b.clone()).aggregate(), Tally([2, 0, 1, 0]));
+    assert_eq!(b.join(a).aggregate(), Tally([2, 0, 1, 0]));
+}
+
+#[test]
+fn display_to_buffer() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+    alice.reply(id, "Hi".to_owned()).unwrap();
+
+    let mut buffer = Vec::new();
+    Detailed::default()
+        .join_root(root)
+        .display_to(&mut buffer)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "Depth: 0
+Author: \"alice\" [0]
+Tags: bug, (1), 
+Body [0]: Data(\"Hello\")
+Reactions: 
+
+Depth: 1
+Author: \"alice\" [65536]
+Tags: 
+Body [0]: Data(\"Hi\")
+Reactions: 
+
+---
+"
+    );
+}
+
+#[test]
+fn thread_tree_shape() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let first = alice.reply(thread.clone(), "First".to_owned()).unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let second = bob.reply(thread.clone(), "Second".to_owned()).unwrap();
+    let nested = bob.reply(first.clone(), "Nested".to_owned()).unwrap();
+    bob.redact(nested.clone(), 0).unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    let node = |id: &MessageID, content, children| ThreadNode {
+        id: id.clone(),
+        author_name: None,
+        content: vec![(0, content)],
+        children,
+    };
+
+    assert_eq!(
+        detailed.thread_tree(&thread),
+        Some(node(
+            &thread,
+            Redactable::Data("Hello".to_owned()),
+            vec![
+                node(
+                    &first,
+                    Redactable::Data("First".to_owned()),
+                    vec![node(&nested, Redactable::Redacted, vec![])]
+                ),
+                node(&second, Redactable::Data("Second".to_owned()), vec![]),
+            ]
+        ))
+    );
+    assert_eq!(detailed.thread_tree(&("carol".to_owned(), 0)), None);
+}
+
+#[test]
+fn display_is_deterministic() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let thread = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut bob = root.clone();
+    let first = Actor::new(bob.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .reply(thread.clone(), "First".to_owned())
+        .unwrap();
+    let mut carol = root.clone();
+    Actor::new(carol.inner.entry_mut("carol"), "carol".to_owned(), 0)
+        .reply(thread, "Second".to_owned())
+        .unwrap();
+    Actor::new(carol.inner.entry_mut("carol"), "carol".to_owned(), 0)
+        .reply(first, "Nested".to_owned())
+        .unwrap();
+
+    let display = |detailed: Detailed| {
+        let mut buffer = Vec::new();
+        detailed.display_to(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    };
+
+    let forwards = display(
+        Detailed::default()
+            .join_root(bob.clone())
+            .join_root(carol.clone()),
+    );
+    let backwards = display(Detailed::default().join_root(carol).join_root(bob));
+    assert_eq!(forwards, backwards);
+
+    // Bob's reply comes before Carol's, and is followed by its own reply.
+    let authors: Vec<_> = forwards
+        .lines()
+        .filter(|line| line.starts_with("Author"))
+        .collect();
+    assert_eq!(
+        authors,
+        [
+            "Author: \"alice\" [0]",
+            "Author: \"bob\" [0]",
+            "Author: \"carol\" [65536]",
+            "Author: \"carol\" [0]",
+        ]
+    );
+}
+
+#[test]
+fn hidden_threads() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let hidden = alice
+        .new_thread("Oops".to_owned(), "Wrong repo".to_owned(), [])
+        .unwrap();
+    let shown = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    alice.set_thread_hidden(hidden.1, true).unwrap();
+    Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .reply(hidden.clone(), "Hi".to_owned())
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(root.clone());
+    assert_eq!(detailed.thread_tree(&hidden), None);
+    assert!(detailed.thread_tree(&shown).is_some());
+
+    let mut buffer = Vec::new();
+    detailed.display_to(&mut buffer).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    assert!(!output.contains("Wrong repo") && !output.contains("Hi"));
+    assert!(output.contains("Hello"));
+
+    // Concurrently, the laptop unhides, whereas the phone unhides and hides
+    // again: the greater count, the phone's, wins.
+    let mut laptop = root.clone();
+    Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .set_thread_hidden(hidden.1, false)
+        .unwrap();
+    let mut phone = root;
+    let mut alice = Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1);
+    alice.set_thread_hidden(hidden.1, false).unwrap();
+    alice.set_thread_hidden(hidden.1, true).unwrap();
+
+    assert!(Detailed::default()
+        .join_root(laptop.clone())
+        .thread_tree(&hidden)
+        .is_some());
+    for root in [laptop.clone().join(phone.clone()), phone.join(laptop)] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(detailed.thread_tree(&hidden), None);
+    }
+}
+
+#[test]
+fn tag_weights() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let id = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.set_tag_weight(id.clone(), "priority".into(), 5);
+    bob.set_tag_weight(id.clone(), "priority".into(), 2);
+
+    // Concurrently, the phone sets a lower weight and the laptop sets a higher
+    // weight, followed by the lower weight again.
+    let mut laptop = root.clone();
+    let mut bob = Actor::new(laptop.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.set_tag_weight(id.clone(), "priority".into(), 4);
+    bob.set_tag_weight(id.clone(), "priority".into(), 1);
+    let mut phone = root;
+    Actor::new(phone.inner.entry_mut("bob"), "bob".to_owned(), 1).set_tag_weight(
+        id.clone(),
+        "priority".into(),
+        3,
+    );
+
+    for root in [laptop.clone().join(phone.clone()), phone.join(laptop)] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(
+            detailed.tag_weights(&id).into_iter().collect::<Vec<_>>(),
+            [(&Tag::from("priority"), 1)]
+        );
+
+        // Votes on tags are unaffected.
+        let comment = detailed.comment(&id).unwrap();
+        assert_eq!(comment.tags.get("bug").unwrap().aggregate().score(), 1);
+        assert_eq!(comment.tags.get("priority"), None);
     }
 }
+
+#[test]
+fn pinned_messages() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let first = alice.reply(thread.clone(), "First".to_owned()).unwrap();
+    let second = alice.reply(thread.clone(), "Second".to_owned()).unwrap();
+    let nested = alice.reply(first.clone(), "Nested".to_owned()).unwrap();
+
+    alice.set_pinned(second.clone(), true);
+    alice.set_pinned(nested.clone(), true);
+    alice.set_pinned(first.clone(), true);
+    alice.set_pinned(first.clone(), false);
+
+    let pinned = |root: Root| Detailed::default().join_root(root).pinned_messages(&thread);
+    assert_eq!(pinned(root.clone()), [second.clone(), nested.clone()]);
+
+    // Concurrently, the laptop re-pins the first message, and the phone
+    // unpins the second.
+    let mut laptop = root.clone();
+    Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .set_pinned(first.clone(), true);
+    let mut phone = root;
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1).set_pinned(second, false);
+
+    assert_eq!(
+        pinned(laptop.clone().join(phone.clone())),
+        [nested.clone(), first.clone()]
+    );
+    assert_eq!(pinned(phone.join(laptop)), [nested, first]);
+}
+
+#[test]
+fn display_names() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 1);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    alice.set_display_name("Alice".to_owned());
+
+    // Concurrently, the phone renames the actor once and the laptop twice.
+    let mut phone = root.clone();
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1)
+        .set_display_name("Alice L.".to_owned());
+    Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .set_display_name("Alice Liddell".to_owned());
+    Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .set_display_name("Alice Liddell".to_owned());
+
+    let detailed = Detailed::default().join_root(root.join(phone));
+    assert_eq!(detailed.display_name("alice"), Some("Alice Liddell"));
+    assert_eq!(detailed.display_name("bob"), None);
+    assert_eq!(
+        detailed.thread_tree(&id).unwrap().author_name.as_deref(),
+        Some("Alice Liddell")
+    );
+
+    let mut buffer = Vec::new();
+    detailed.display_to(&mut buffer).unwrap();
+    assert!(String::from_utf8(buffer)
+        .unwrap()
+        .contains("Author: Alice Liddell (\"alice\") [1]"));
+}
+
+#[test]
+fn threads_by_time() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let late = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .with_clock(|| 2000)
+        .new_thread("Late".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let early = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .with_clock(|| 1000)
+        .new_thread("Early".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let tied = Actor::new(root.inner.entry_mut("carol"), "carol".to_owned(), 0)
+        .with_clock(|| 1000)
+        .new_thread("Tied".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let unknown = Actor::new(root.inner.entry_mut("dave"), "dave".to_owned(), 0)
+        .new_thread("Unknown".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(detailed.created_at(&early), Some(1000));
+    assert_eq!(detailed.created_at(&unknown), None);
+    assert_eq!(detailed.threads_by_time(), [unknown, early, tied, late]);
+}
+
+#[test]
+fn search() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "The build is broken".to_owned(), [])
+        .unwrap();
+    let redacted = alice
+        .reply(thread.clone(), "My password is hunter2".to_owned())
+        .unwrap();
+    alice.redact(redacted.clone(), 0).unwrap();
+    let edited = alice
+        .reply(thread.clone(), "Fixed the BUILD".to_owned())
+        .unwrap();
+    alice.edit(edited.1, "Fixed it".to_owned()).unwrap();
+    let reply = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .reply(thread.clone(), "Which build?".to_owned())
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(
+        detailed.search("Build"),
+        [thread.clone(), edited.clone(), reply]
+    );
+    assert_eq!(detailed.search("fixed it"), [edited]);
+    assert_eq!(detailed.search("hunter2"), []);
+    assert_eq!(detailed.search("deploy"), []);
+}
+
+#[test]
+fn current_content() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello wrold".to_owned(), [])
+        .unwrap();
+    let current = |root: &Root| {
+        Detailed::default()
+            .join_root(root.clone())
+            .current_content(&id)
+            .map(str::to_owned)
+    };
+    assert_eq!(current(&root).as_deref(), Some("Hello wrold"));
+
+    // Redacting an older version leaves the latest.
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let version = alice.edit(id.1, "Hello world".to_owned()).unwrap();
+    alice.redact(id.clone(), 0).unwrap();
+    assert_eq!(current(&root).as_deref(), Some("Hello world"));
+
+    // Redacting the latest does not reveal an older version.
+    let mut redacted = root.clone();
+    Actor::new(redacted.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .redact(id.clone(), version)
+        .unwrap();
+    assert_eq!(current(&redacted), None);
+
+    // Of concurrent edits, the device with the greatest ID wins.
+    let mut laptop = root.clone();
+    Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 2)
+        .edit(id.1, "Hello, world".to_owned())
+        .unwrap();
+    let mut phone = root;
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1)
+        .edit(id.1, "Hello world!".to_owned())
+        .unwrap();
+    assert_eq!(
+        current(&phone.join(laptop)).as_deref(),
+        Some("Hello, world")
+    );
+}
+
+#[test]
+fn redaction_reasons() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let id = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "My password is hunter2".to_owned(), [])
+        .unwrap();
+
+    let mut laptop = root.clone();
+    Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .redact_with_reason(id.clone(), 0, "Leaked a password".to_owned())
+        .unwrap();
+    let mut phone = root.clone();
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1)
+        .redact_with_reason(id.clone(), 0, "Oops".to_owned())
+        .unwrap();
+
+    // The redaction wins over the data, which a stale replica still has.
+    let detailed = Detailed::default().join_root(root.join(laptop.clone()));
+    assert_eq!(detailed.current_content(&id), None);
+    assert_eq!(detailed.redaction_reasons(&id, 0), ["Leaked a password"]);
+
+    let detailed = Detailed::default().join_root(laptop.join(phone));
+    assert_eq!(
+        detailed.redaction_reasons(&id, 0),
+        ["Leaked a password", "Oops"]
+    );
+    assert!(detailed.redaction_reasons(&id, 1).is_empty());
+}
+
+#[test]
+fn attachments() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "See attached".to_owned(), [])
+        .unwrap();
+    let screenshot = alice
+        .attach(id.1, "image/png".to_owned(), "e69de29b".to_owned())
+        .unwrap();
+    let log = alice
+        .attach(id.1, "text/plain".to_owned(), "d670460b".to_owned())
+        .unwrap();
+    assert_ne!(screenshot, log);
+
+    let detailed = Detailed::default().join_root(root.clone());
+    assert_eq!(
+        detailed
+            .attachments(&id)
+            .into_iter()
+            .map(|(key, attachment)| (key, attachment.content_type.as_str()))
+            .collect::<Vec<_>>(),
+        [(screenshot, "image/png"), (log, "text/plain")]
+    );
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    assert!(bob.redact_attachment(id.clone(), screenshot).is_err());
+
+    let mut redacted = root.clone();
+    Actor::new(redacted.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .redact_attachment(id.clone(), screenshot)
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(root.join(redacted));
+    assert_eq!(
+        detailed
+            .attachments(&id)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>(),
+        [log]
+    );
+}
+
+#[test]
+fn content_types() {
+    use crate::Actor;
+
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let plain = alice
+        .new_thread("Plain".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let id = alice
+        .new_thread("Markdown".to_owned(), "*Hello*".to_owned(), [])
+        .unwrap();
+    alice
+        .set_content_type(id.1, 0, "text/markdown".to_owned())
+        .unwrap();
+    let edited = alice.edit(id.1, "**Hello**".to_owned()).unwrap();
+    let html = alice.edit(id.1, "<b>Hello</b>".to_owned()).unwrap();
+    alice
+        .set_content_type(id.1, html, "text/html".to_owned())
+        .unwrap();
+
+    let mut buffer = Vec::new();
+    minicbor::encode(&slice, &mut buffer).unwrap();
+    let mut root = Root::default();
+    root.inner
+        .insert("alice".to_owned(), minicbor::decode(&buffer).unwrap());
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(detailed.content_type(&plain, 0), "text/plain");
+    assert_eq!(detailed.content_type(&id, 0), "text/markdown");
+    assert_eq!(detailed.content_type(&id, edited), "text/markdown");
+    assert_eq!(detailed.content_type(&id, html), "text/html");
+}
+
+#[test]
+fn dangling_refs() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let reply = bob.reply(thread.clone(), "Hi".to_owned()).unwrap();
+    bob.reply(reply.clone(), "Again".to_owned()).unwrap();
+
+    // Only bob's slice, without the thread it replies to.
+    let mut partial = Root::default();
+    partial
+        .inner
+        .insert("bob".to_owned(), root.inner.get("bob").unwrap().clone());
+
+    let detailed = Detailed::default().join_root(partial);
+    assert_eq!(detailed.dangling_refs(), vec![thread.clone()]);
+
+    let mut buffer = Vec::new();
+    detailed.display_to(&mut buffer).unwrap();
+    assert!(buffer.is_empty());
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(detailed.dangling_refs(), vec![]);
+    detailed.display_to(&mut buffer).unwrap();
+    assert!(String::from_utf8(buffer).unwrap().contains("Again"));
+}
+
+#[test]
+fn replies_to_many() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let first = alice
+        .new_thread("First".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let second = alice
+        .new_thread("Second".to_owned(), "World".to_owned(), [])
+        .unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let reply = bob
+        .reply_to_many([first.clone(), second.clone()], "Both".to_owned())
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    for thread in [first, second] {
+        let children = detailed.thread_tree(&thread).unwrap().children;
+        assert_eq!(
+            children.iter().map(|node| &node.id).collect::<Vec<_>>(),
+            [&reply]
+        );
+    }
+
+    let mut buffer = Vec::new();
+    detailed.display_to(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap().matches("Both").count(),
+        2
+    );
+}
+
+#[test]
+fn cycles_terminate() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    // `a` replies to `b`, which has not been written yet, and `b` to `a`.
+    let b = ("alice".to_owned(), 2 << 16);
+    let a = alice
+        .reply_to_many([thread.clone(), b.clone()], "A".to_owned())
+        .unwrap();
+    assert_eq!(alice.reply(a.clone(), "B".to_owned()).unwrap(), b);
+    alice.set_pinned(b.clone(), true);
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(detailed.cycles(), vec![vec![a.clone(), b.clone()]]);
+
+    let mut buffer = Vec::new();
+    detailed.display_to(&mut buffer).unwrap();
+    let output = String::from_utf8(buffer).unwrap();
+    assert_eq!(output.matches("Body").count(), 3);
+
+    let tree = detailed.thread_tree(&thread).unwrap();
+    assert_eq!(tree.children[0].children[0].id, b);
+    assert!(tree.children[0].children[0].children.is_empty());
+
+    assert_eq!(detailed.pinned_messages(&thread), vec![b]);
+}
+
+#[test]
+fn retitles() {
+    use crate::Actor;
+
+    let mut laptop = Root::default();
+    let mut alice = Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let reply = alice.reply(id.clone(), "Hi".to_owned()).unwrap();
+
+    let mut phone = laptop.clone();
+    alice = Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    alice.retitle(id.1, "Laptop".to_owned()).unwrap();
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1)
+        .retitle(id.1, "Phone".to_owned())
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(laptop.clone().join(phone.clone()));
+    assert_eq!(detailed.titles(&id), ["Laptop", "Phone"]);
+    assert_eq!(
+        detailed.thread_title(&id),
+        Some(TitleState::Conflict(vec!["Laptop", "Phone"]))
+    );
+    assert_eq!(detailed.thread_title(&reply), None);
+
+    // A further rename, being a later version, supersedes both.
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1)
+        .retitle(id.1, "Later".to_owned())
+        .unwrap();
+    for root in [laptop.clone().join(phone.clone()), phone.join(laptop)] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(detailed.titles(&id), ["Later"]);
+        assert_eq!(
+            detailed.thread_title(&id),
+            Some(TitleState::Resolved("Later"))
+        );
+    }
+}
+
+#[test]
+fn threads_page() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let first = alice
+        .new_thread("First".to_owned(), "1".to_owned(), ["bug".into()])
+        .unwrap();
+    let second = alice
+        .new_thread("Second".to_owned(), "2".to_owned(), [])
+        .unwrap();
+    let third = alice
+        .new_thread("Third".to_owned(), "3".to_owned(), ["bug".into()])
+        .unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0).with_clock(|| 100);
+    let reply = bob.reply(second.clone(), "Hi".to_owned()).unwrap();
+    bob.reply(reply, "Again".to_owned()).unwrap();
+    bob.adjust_tags(third.clone(), ["bug".into(), "ui".into()], [])
+        .unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    let ids = |sort, offset, limit| {
+        detailed
+            .threads_page(offset, limit, sort)
+            .into_iter()
+            .map(|summary| summary.id.1)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids(ThreadSort::Id, 0, 10), [first.1, second.1, third.1]);
+    assert_eq!(ids(ThreadSort::Id, 1, 1), [second.1]);
+    assert_eq!(ids(ThreadSort::Id, 2, 10), [third.1]);
+    assert!(ids(ThreadSort::Id, 3, 10).is_empty());
+    assert!(ids(ThreadSort::Id, 0, 0).is_empty());
+
+    assert_eq!(
+        ids(ThreadSort::LatestActivity, 0, 10),
+        [second.1, first.1, third.1]
+    );
+    assert_eq!(
+        ids(ThreadSort::TagScore, 0, 10),
+        [third.1, first.1, second.1]
+    );
+    assert_eq!(ids(ThreadSort::TagScore, 1, 1), [first.1]);
+
+    let summary = detailed.threads_page(0, 3, ThreadSort::Id);
+    assert_eq!(
+        summary[1],
+        ThreadSummary {
+            id: second,
+            titles: vec!["Second".into()],
+            reply_count: 2,
+            top_tags: vec![],
+        }
+    );
+    assert_eq!(
+        summary[2].top_tags,
+        [(Tag::from("bug"), 2), (Tag::from("ui"), 1)]
+    );
+}
+
+#[test]
+fn thread_stats() {
+    use crate::Actor;
+
+    static TIME: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    fn clock() -> u64 {
+        TIME.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    }
+
+    let mut root = Root::default();
+    let mut alice =
+        Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0).with_clock(clock);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let first = alice.reply(thread.clone(), "First".to_owned()).unwrap();
+    let nested = alice.reply(first.clone(), "Nested".to_owned()).unwrap();
+    let hidden = alice.reply(nested.clone(), "Hidden".to_owned()).unwrap();
+    alice.set_thread_hidden(hidden.1, true).unwrap();
+    let second = alice.reply(thread.clone(), "Second".to_owned()).unwrap();
+
+    let detailed = Detailed::default().join_root(root.clone());
+    assert_eq!(
+        detailed.thread_stats(&thread),
+        Some(ThreadStats {
+            reply_count: 3,
+            last_activity: Activity {
+                time: Some(4),
+                version: 0,
+                message: second.clone(),
+            },
+        })
+    );
+    assert_eq!(
+        detailed.thread_stats(&first),
+        Some(ThreadStats {
+            reply_count: 1,
+            last_activity: Activity {
+                time: Some(2),
+                version: 0,
+                message: nested.clone(),
+            },
+        })
+    );
+    assert_eq!(detailed.thread_stats(&hidden), None);
+
+    // Editing an older reply is the latest activity.
+    let mut alice =
+        Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0).with_clock(clock);
+    let version = alice.edit(first.1, "First!".to_owned()).unwrap();
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(
+        detailed.thread_stats(&thread).unwrap().last_activity,
+        Activity {
+            time: Some(5),
+            version,
+            message: first.clone(),
+        }
+    );
+
+    // Without a clock, the latest version of the content counts.
+    let mut root = Root::default();
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let thread = bob
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let first = bob.reply(thread.clone(), "First".to_owned()).unwrap();
+    bob.reply(thread.clone(), "Second".to_owned()).unwrap();
+    let version = bob.edit(first.1, "First!".to_owned()).unwrap();
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(
+        detailed.thread_stats(&thread).unwrap().last_activity,
+        Activity {
+            time: None,
+            version,
+            message: first,
+        }
+    );
+}
+
+#[test]
+fn threads_by_actor() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let first = alice
+        .new_thread("First".to_owned(), "1".to_owned(), [])
+        .unwrap();
+    let second = alice
+        .new_thread("Second".to_owned(), "2".to_owned(), [])
+        .unwrap();
+    alice
+        .new_thread("Third".to_owned(), "3".to_owned(), [])
+        .unwrap();
+    let reply = alice.reply(second.clone(), "Hi".to_owned()).unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.reply(reply, "Nested".to_owned()).unwrap();
+    bob.reply(first.clone(), "Hi".to_owned()).unwrap();
+    let mut carol = Actor::new(root.inner.entry_mut("carol"), "carol".to_owned(), 0);
+    carol.reply(first.clone(), "Hi".to_owned()).unwrap();
+
+    let detailed = Detailed::default().join_root(root.clone());
+    assert_eq!(detailed.threads_by_actor("bob"), [first.clone(), second]);
+    assert_eq!(detailed.threads_by_actor("carol"), vec![first]);
+    assert!(detailed.threads_by_actor("dave").is_empty());
+
+    // Without alice's slice, bob's replies belong to no known thread.
+    root.inner.retain(|actor, _| actor != "alice");
+    let detailed = Detailed::default().join_root(root);
+    assert!(detailed.threads_by_actor("bob").is_empty());
+}
+
+#[test]
+fn message_history() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let typo = alice.edit(id.1, "Hello wrold".to_owned()).unwrap();
+    let fixed = alice.edit(id.1, "Hello world".to_owned()).unwrap();
+    alice.redact(id.clone(), typo).unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(
+        detailed.message_history(&id).collect::<Vec<_>>(),
+        [
+            (0, &Redactable::Data("Hello".to_owned())),
+            (typo, &Redactable::Redacted),
+            (fixed, &Redactable::Data("Hello world".to_owned())),
+        ]
+    );
+    assert_eq!(detailed.message_history(&("bob".to_owned(), 0)).count(), 0);
+}
+
+#[test]
+fn merged_threads() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let target = alice
+        .new_thread("Crash".to_owned(), "It crashes".to_owned(), [])
+        .unwrap();
+    let first = alice
+        .reply(target.clone(), "On startup".to_owned())
+        .unwrap();
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let source = bob
+        .new_thread("Panic".to_owned(), "It panics".to_owned(), [])
+        .unwrap();
+    let second = bob.reply(source.clone(), "Backtrace".to_owned()).unwrap();
+
+    let mut merged = root.clone();
+    Actor::new(merged.inner.entry_mut("carol"), "carol".to_owned(), 0)
+        .merge_thread_into(source.clone(), target.clone());
+    let detailed = Detailed::default().join_root(merged.clone());
+
+    // The source is listed as a reply to the target, along with its replies.
+    assert_eq!(detailed.canonical_thread(&source), &target);
+    assert_eq!(detailed.threads_by_time(), vec![target.clone()]);
+    let tree = detailed.thread_tree(&target).unwrap();
+    assert_eq!(
+        tree.children
+            .iter()
+            .map(|child| (&child.id, child.children.len()))
+            .collect::<Vec<_>>(),
+        [(&first, 0), (&source, 1)]
+    );
+    assert_eq!(tree.children[1].children[0].id, second);
+    assert_eq!(detailed.thread_stats(&target).unwrap().reply_count, 3);
+
+    // Of concurrent merges of the same thread, the same one wins on every
+    // replica.
+    let mut other = root.clone();
+    let third = Actor::new(other.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Abort".to_owned(), "It aborts".to_owned(), [])
+        .unwrap();
+    Actor::new(other.inner.entry_mut("dave"), "dave".to_owned(), 0)
+        .merge_thread_into(source.clone(), third.clone());
+    for root in [merged.clone().join(other.clone()), other.join(merged)] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(detailed.canonical_thread(&source), &third);
+        assert_eq!(detailed.threads_by_time(), [target.clone(), third.clone()]);
+        assert_eq!(detailed.thread_tree(&target).unwrap().children.len(), 1);
+    }
+
+    // Threads merged into each other settle on the least of them.
+    Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .merge_thread_into(target.clone(), source.clone());
+    Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .merge_thread_into(source.clone(), target.clone());
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(detailed.canonical_thread(&source), &target);
+    assert_eq!(detailed.canonical_thread(&target), &target);
+    assert_eq!(detailed.threads_by_time(), vec![target.clone()]);
+    assert_eq!(detailed.thread_stats(&target).unwrap().reply_count, 3);
+}
+
+#[test]
+fn quotes() {
+    use crate::{Actor, Quote};
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let version = alice.edit(thread.1, "Hello world".to_owned()).unwrap();
+
+    let mut other = root.clone();
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let reply = bob
+        .reply_quoting(thread.clone(), version, Some((6, 11)), "Which?".to_owned())
+        .unwrap();
+    let mut carol = Actor::new(other.inner.entry_mut("carol"), "carol".to_owned(), 0);
+    let whole = carol
+        .reply_quoting(thread.clone(), 0, None, "Hi".to_owned())
+        .unwrap();
+
+    for root in [root.clone().join(other.clone()), other.join(root)] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(
+            detailed.quotes(&reply),
+            [&Quote {
+                message: thread.clone(),
+                version,
+                range: Some((6, 11)),
+            }]
+        );
+        assert_eq!(
+            detailed.quotes(&whole),
+            [&Quote {
+                message: thread.clone(),
+                version: 0,
+                range: None,
+            }]
+        );
+        assert!(detailed.quotes(&thread).is_empty());
+        assert_eq!(detailed.thread_stats(&thread).unwrap().reply_count, 2);
+    }
+}
+
+#[test]
+fn reaction_weights() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    alice.react(id.clone(), ":+1:".into(), true);
+    alice.react_weighted(id.clone(), "boost".into(), 3);
+
+    let mut other = root.clone();
+    let mut bob = Actor::new(other.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.react_weighted(id.clone(), "boost".into(), 10);
+    bob.react_weighted(id.clone(), "boost".into(), 5);
+    bob.react_weighted(id.clone(), "rocket".into(), 1);
+    bob.react_weighted(id.clone(), "rocket".into(), 0);
+
+    for root in [root.clone().join(other.clone()), other.join(root)] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(
+            detailed
+                .reaction_weights(&id)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [(&Reaction::from("boost"), 8)]
+        );
+        // Binary reactions are counted separately.
+        assert_eq!(
+            detailed
+                .comment(&id)
+                .unwrap()
+                .reactions
+                .keys()
+                .collect::<Vec<_>>(),
+            [&Reaction::from(":+1:")]
+        );
+    }
+}
+
+#[test]
+fn join_slice() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+    alice.set_display_name("Alice".to_owned());
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let reply = bob.reply(thread.clone(), "Hi".to_owned()).unwrap();
+    bob.react(thread.clone(), ":+1:".into(), true);
+    bob.adjust_tags(thread.clone(), ["ui".into()], []).unwrap();
+
+    let mut incremental = Detailed::default();
+    for (actor, slice) in root.inner.clone() {
+        incremental.join_slice(actor, slice);
+    }
+    assert_eq!(incremental, Detailed::default().join_root(root.clone()));
+
+    // An actor's updated slice replaces neither their earlier writes nor
+    // anyone else's.
+    let mut carol = Actor::new(root.inner.entry_mut("carol"), "carol".to_owned(), 0);
+    carol.reply(reply.clone(), "Hey".to_owned()).unwrap();
+    carol.react(reply, ":tada:".into(), true);
+    incremental.join_slice("carol".to_owned(), root.inner.get("carol").unwrap().clone());
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    alice.edit(thread.1, "Hello!".to_owned()).unwrap();
+    alice.set_pinned(thread, true);
+    incremental.join_slice("alice".to_owned(), root.inner.get("alice").unwrap().clone());
+
+    assert_eq!(incremental, Detailed::default().join_root(root));
+}
+
+#[test]
+fn votes_are_attributed_to_voters() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let thread = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.react(thread.clone(), ":+1:".into(), true);
+    bob.adjust_tags(thread.clone(), ["bug".into()], []).unwrap();
+    bob.set_tag_weight(thread.clone(), "bug".into(), 3);
+    bob.react_weighted(thread.clone(), "boost".into(), 2);
+    let reply = bob.reply(thread.clone(), "Hi".to_owned()).unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    let comment = detailed.comment(&thread).unwrap();
+    fn voters<V: Semilattice>(votes: &MapLattice<ActorID, V>) -> Vec<&str> {
+        votes.keys().map(String::as_str).collect()
+    }
+    assert_eq!(voters(&comment.reactions.get(":+1:").unwrap().0), ["bob"]);
+    assert_eq!(voters(&comment.tags.get("bug").unwrap().0), ["bob"]);
+    assert_eq!(voters(comment.tag_weights.get("bug").unwrap()), ["bob"]);
+    let weights = comment.reaction_weights.as_ref().unwrap();
+    assert_eq!(voters(weights.get("boost").unwrap()), ["bob"]);
+    assert_eq!(comment.responses.keys().collect::<Vec<_>>(), [&reply]);
+
+    // Nothing is stored under bob but his reply.
+    assert_eq!(
+        detailed
+            .comments
+            .get("bob")
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        [&reply.1]
+    );
+}
+
+#[test]
+fn redactions() {
+    use crate::Actor;
+
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let stale = root.clone();
+    let mut phone = root.clone();
+    Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .redact_at(id.clone(), 0, 20)
+        .unwrap();
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1)
+        .redact_at(id.clone(), 0, 10)
+        .unwrap();
+
+    // Concurrent redactions are all recorded, and win over stale data.
+    for root in [
+        root.clone().join(phone.clone()).join(stale.clone()),
+        stale.join(phone).join(root),
+    ] {
+        let detailed = Detailed::default().join_root(root);
+        assert_eq!(detailed.current_content(&id), None);
+        assert_eq!(
+            detailed.redactions(&id, 0),
+            [
+                &Redaction {
+                    by: "alice".to_owned(),
+                    at: 10
+                },
+                &Redaction {
+                    by: "alice".to_owned(),
+                    at: 20
+                },
+            ]
+        );
+    }
+
+    // Redacting is only recorded when the actor has a clock.
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let unrecorded = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    alice.redact(unrecorded.clone(), 0).unwrap();
+    let mut alice = alice.with_clock(|| 30);
+    let recorded = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    alice.redact(recorded.clone(), 0).unwrap();
+
+    let detailed = Detailed::default().join_root(root);
+    assert_eq!(detailed.current_content(&unrecorded), None);
+    assert!(detailed.redactions(&unrecorded, 0).is_empty());
+    assert_eq!(
+        detailed.redactions(&recorded, 0),
+        [&Redaction {
+            by: "alice".to_owned(),
+            at: 30
+        }]
+    );
+}
@@ -0,0 +1,150 @@
+use core::fmt;
+
+use crate::{ActorID, MessageID};
+
+/// Errors encountered whilst persisting threads to, or restoring threads from,
+/// a git repository.
+#[derive(Debug)]
+pub enum ThreadsError {
+    Git(git2::Error),
+    Encode(minicbor::encode::Error<<Vec<u8> as minicbor::encode::Write>::Error>),
+    Decode(minicbor::decode::Error),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    /// A blob written with a schema version this crate cannot read.
+    UnsupportedSchema(u16),
+    /// A tree entry whose name is not valid UTF-8, thus not an actor ID.
+    InvalidActorName,
+    /// An actor whose slice was to be saved, but who has none.
+    UnknownActor(ActorID),
+    /// An actor's slice which decoded, but which does not validate.
+    Invalid(ActorID, ValidationError),
+}
+
+impl fmt::Display for ThreadsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Git(err) => write!(f, "git error: {}", err),
+            Self::Encode(err) => write!(f, "failed to CBOR encode: {}", err),
+            Self::Decode(err) => write!(f, "failed to CBOR decode: {}", err),
+            #[cfg(feature = "serde")]
+            Self::Json(err) => write!(f, "invalid JSON: {}", err),
+            Self::UnsupportedSchema(version) => {
+                write!(f, "unsupported schema version: {}", version)
+            }
+            Self::InvalidActorName => write!(f, "invalid actor name in tree"),
+            Self::UnknownActor(actor) => write!(f, "no slice for actor: {}", actor),
+            Self::Invalid(actor, err) => write!(f, "invalid slice of {}: {}", actor, err),
+        }
+    }
+}
+
+impl std::error::Error for ThreadsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Git(err) => Some(err),
+            Self::Invalid(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<git2::Error> for ThreadsError {
+    fn from(err: git2::Error) -> Self {
+        Self::Git(err)
+    }
+}
+
+impl From<minicbor::encode::Error<<Vec<u8> as minicbor::encode::Write>::Error>> for ThreadsError {
+    fn from(err: minicbor::encode::Error<<Vec<u8> as minicbor::encode::Write>::Error>) -> Self {
+        Self::Encode(err)
+    }
+}
+
+impl From<minicbor::decode::Error> for ThreadsError {
+    fn from(err: minicbor::decode::Error) -> Self {
+        Self::Decode(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for ThreadsError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Errors encountered whilst an actor changes a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActorError {
+    /// The message was written by another actor, so cannot be changed.
+    NotOwned(MessageID),
+    /// The message replied to is not known to this actor.
+    UnknownParent(MessageID),
+    /// The message changed is not among the actor's messages.
+    UnknownMessage(MessageID),
+    /// The message is a reply, rather than a thread.
+    NotAThread(MessageID),
+    /// Content of the given length in bytes, which exceeds `Limits`.
+    ContentTooLong(usize),
+    /// The given number of tags on a message, which exceeds `Limits`.
+    TooManyTags(usize),
+    /// The actor has already written as many messages as `Limits` allows.
+    TooManyMessages,
+}
+
+impl fmt::Display for ActorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotOwned((actor, id)) => {
+                write!(f, "message {} is owned by another actor, {}", id, actor)
+            }
+            Self::UnknownParent((actor, id)) => {
+                write!(f, "unknown parent message {} by {}", id, actor)
+            }
+            Self::UnknownMessage((actor, id)) => {
+                write!(f, "unknown message {} by {}", id, actor)
+            }
+            Self::NotAThread((actor, id)) => {
+                write!(f, "message {} by {} is not a thread", id, actor)
+            }
+            Self::ContentTooLong(len) => write!(f, "content of {} bytes is too long", len),
+            Self::TooManyTags(count) => write!(f, "{} tags on one message are too many", count),
+            Self::TooManyMessages => write!(f, "too many messages"),
+        }
+    }
+}
+
+impl std::error::Error for ActorError {}
+
+/// Violations of the invariants of a slice, as found by `Slice::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A reply to the message which is not among the actor's messages.
+    DanglingReply(MessageID, u64),
+    /// A message without any content.
+    EmptyContent(u64),
+    /// A vote on the message whose counter is too great to be advanced.
+    VoteOverflow(MessageID),
+    /// A vote on the message whose counter is not any of its choices.
+    InvalidVote(MessageID),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DanglingReply((actor, id), reply) => {
+                write!(f, "unknown reply {} to message {} by {}", reply, id, actor)
+            }
+            Self::EmptyContent(id) => write!(f, "message {} has no content", id),
+            Self::VoteOverflow((actor, id)) => {
+                write!(f, "vote counter overflows on message {} by {}", id, actor)
+            }
+            Self::InvalidVote((actor, id)) => {
+                write!(f, "invalid vote on message {} by {}", id, actor)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
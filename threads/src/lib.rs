@@ -1,22 +1,118 @@
-use semilog::{MapLattice, Max, Redactable, Semilattice, SetLattice, VecLattice};
+use core::{borrow::Borrow, fmt, mem};
+use std::collections::BTreeSet;
+
+use semilog::{
+    GuardedPair, LWWRegister, MapLattice, Max, Redactable, Semilattice, SetLattice, VecLattice,
+};
+
+use detailed::{Detailed, ReactionVote, TagVote, Vote};
+
+/// Emit a `tracing` event at the given level, or nothing at all without the
+/// `tracing` feature.
+macro_rules! event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(tracing::Level::$level, $($arg)*);
+    };
+}
 
 pub mod detailed;
+mod error;
+mod schema;
+
+pub use error::{ActorError, ThreadsError, ValidationError};
+pub use schema::{decode_slice, CURRENT_SCHEMA, MAGIC};
 
 /// An actor ID. Probably a public key.
 pub type ActorID = String;
 
+/// A device ID. Each device of an actor must use a distinct ID.
+pub type DeviceID = u16;
+
 /// A Message ID. An actor ID paired with a supposedly unique number. The actor
-/// is responsible for choosing a unique number.
+/// is responsible for choosing a unique number, which is a per-device counter
+/// in the high bits and the device ID in the low 16 bits.
 pub type MessageID = (ActorID, u64);
 
-pub type Reaction = String;
-pub type Tag = String;
+/// Define a newtype of `String`, which is encoded exactly as the string is.
+macro_rules! string_newtype {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(
+            Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, minicbor::Encode, minicbor::Decode,
+        )]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(transparent)
+        )]
+        #[cbor(transparent)]
+        pub struct $name(#[n(0)] pub String);
+
+        impl From<String> for $name {
+            fn from(name: String) -> Self {
+                Self(name)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(name: &str) -> Self {
+                Self(name.to_owned())
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+string_newtype! {
+    /// A reaction to a message, e.g. `:+1:`.
+    Reaction
+}
+
+string_newtype! {
+    /// A tag of a message, e.g. `bug`. Distinct from a `Reaction`:
+    ///
+    /// ```compile_fail
+    /// # use threads::{Actor, Reaction, Slice};
+    /// let mut slice = Slice::default();
+    /// let mut actor = Actor::new(&mut slice, "alice".to_owned(), 0);
+    /// let id = actor
+    ///     .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+    ///     .unwrap();
+    /// actor.adjust_tags(id, [Reaction::from(":+1:")], []);
+    /// ```
+    Tag
+}
 
 pub type Oid = Vec<u8>;
 
 #[derive(
     Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, minicbor::Encode, minicbor::Decode,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Patchset {
     #[n(0)]
     target: Option<String>,
@@ -27,247 +123,2331 @@ struct Patchset {
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Semilattice, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Owned {
     #[n(0)]
     titles: VecLattice<SetLattice<String>>,
+    /// Versions of the content, keyed by a version number in the high bits and
+    /// the editing device in the low 16 bits, such that concurrent edits from
+    /// different devices are all retained.
     #[n(1)]
-    content: VecLattice<Redactable<String>>,
+    content: MapLattice<u64, Redactable<String>>,
     #[n(2)]
     commits: VecLattice<SetLattice<Patchset>>,
+    /// Whether the author has hidden the thread, as a vote where 0 is visible
+    /// and 1 is hidden. Optional, so that older slices still decode.
+    #[n(3)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    hidden: Option<Max<u64>>,
+    /// When the message was created, as asserted by its author.
+    #[n(4)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    created_at: Option<Max<u64>>,
+    /// Why versions of the content were redacted, keyed like `content`.
+    #[n(5)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    redaction_reasons: Option<MapLattice<u64, SetLattice<String>>>,
+    /// Attached files, keyed like messages by a counter in the high bits and
+    /// the attaching device in the low 16 bits.
+    #[n(6)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    attachments: Option<MapLattice<u64, Redactable<Attachment>>>,
+    /// The content type of versions of the content, keyed like `content`.
+    /// Versions without one are `DEFAULT_CONTENT_TYPE`.
+    #[n(7)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    content_types: Option<MapLattice<u64, ContentType>>,
+    /// The spans of other messages which this message quotes.
+    #[n(8)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    quotes: Option<SetLattice<Quote>>,
+    /// Who redacted versions of the content and when, keyed like `content`.
+    #[n(9)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    redactions: Option<MapLattice<u64, SetLattice<Redaction>>>,
+    /// When the content was last edited, as asserted by its author.
+    #[n(10)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    edited_at: Option<Max<u64>>,
+}
+
+/// The MIME type of a version of a message's content, e.g. `text/markdown`.
+pub type ContentType = LWWRegister<DeviceID, String>;
+
+/// The content type of content which was not given one.
+pub const DEFAULT_CONTENT_TYPE: &str = "text/plain";
+
+/// A record of the redaction of a version of a message, for auditing.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Redaction {
+    #[n(0)]
+    pub by: ActorID,
+    /// When it was redacted, as per `Actor::clock`.
+    #[n(1)]
+    pub at: u64,
+}
+
+/// A span of a version of a message, as quoted by a reply.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quote {
+    #[n(0)]
+    pub message: MessageID,
+    /// The key of the quoted version of the content.
+    #[n(1)]
+    pub version: u64,
+    /// The start and end of the quoted characters, or `None` if the whole
+    /// version is quoted.
+    #[n(2)]
+    pub range: Option<(u32, u32)>,
+}
+
+/// A file attached to a message, stored as a git blob rather than inline.
+#[derive(Debug, Clone, PartialEq, Eq, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attachment {
+    /// The MIME type, e.g. `image/png`.
+    #[n(0)]
+    pub content_type: String,
+    /// The hex ID of the blob.
+    #[n(1)]
+    pub blob_oid: String,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Semilattice, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Shared {
     #[n(0)]
     responses: SetLattice<u64>,
     #[n(1)]
     tags: MapLattice<Tag, Max<u64>>,
     #[n(2)]
-    reactions: MapLattice<Tag, Max<u64>>,
+    reactions: MapLattice<Reaction, Max<u64>>,
+    /// The weight of each tag, e.g. a priority, where the latest version wins.
+    #[n(3)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    tag_weights: Option<MapLattice<Tag, TagWeight>>,
+    /// When the message was last pinned or unpinned, as a logical clock which
+    /// is odd when pinned.
+    #[n(4)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pinned: Option<Max<u64>>,
+    /// The thread into which this thread was merged, if any.
+    #[n(5)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    merged_into: Option<Redirect>,
+    /// The weight of each weighted reaction, e.g. a boost, where the latest
+    /// version wins.
+    #[n(6)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    reaction_weights: Option<MapLattice<Reaction, ReactionWeight>>,
 }
 
+/// A weight versioned by a counter. Of concurrent updates with the same
+/// version, the greatest weight wins.
+pub type TagWeight = GuardedPair<Max<u64>, Max<u8>>;
+
+/// The weight of a reaction, versioned like `TagWeight`.
+pub type ReactionWeight = GuardedPair<Max<u64>, Max<u64>>;
+
+/// The root of the thread into which a thread was merged, written by an actor
+/// at a logical time. Of concurrent redirects with the same time, that of the
+/// greatest actor wins, and then that to the greatest thread.
+pub type Redirect = LWWRegister<ActorID, MessageID>;
+
 #[derive(Clone, Default, Debug, PartialEq, Semilattice, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Slice {
     #[n(0)]
-    owned: VecLattice<Owned>,
+    owned: MapLattice<u64, Owned>,
     #[n(1)]
     shared: MapLattice<ActorID, MapLattice<u64, Shared>>,
+    #[n(2)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    display_name: Option<DisplayName>,
+}
+
+/// A name chosen by an actor for themselves, written by one of their devices.
+pub type DisplayName = LWWRegister<DeviceID, String>;
+
+impl Slice {
+    /// The part of this slice which `other` is missing, such that
+    /// `other.join(self.delta_since(&other)) == other.join(self)`. Replicas can
+    /// exchange deltas rather than whole slices.
+    pub fn delta_since(&self, other: &Slice) -> Slice {
+        let empty = MapLattice::default();
+
+        Slice {
+            owned: self.owned.delta_since(&other.owned),
+            shared: self
+                .shared
+                .iter()
+                .map(|(actor, shared)| {
+                    let other = other.shared.get(actor).unwrap_or(&empty);
+                    (actor.clone(), shared.delta_since(other))
+                })
+                .filter(|(_, delta)| !delta.is_empty())
+                .collect(),
+            display_name: if self.display_name <= other.display_name {
+                None
+            } else {
+                self.display_name.clone()
+            },
+        }
+    }
+
+    /// Check the invariants which every slice written by an `Actor` upholds,
+    /// but which a malformed or malicious slice may not, despite decoding.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        // Messages are written along with their content.
+        for (id, owned) in &self.owned {
+            if owned.content.is_empty() {
+                return Err(ValidationError::EmptyContent(*id));
+            }
+        }
+
+        for (actor, comments) in &self.shared {
+            for (id, shared) in comments {
+                let parent = || (actor.clone(), *id);
+
+                if let Some(reply) = shared
+                    .responses
+                    .keys()
+                    .find(|reply| !self.owned.contains_key(*reply))
+                {
+                    return Err(ValidationError::DanglingReply(parent(), *reply));
+                }
+
+                if shared.tags.values().any(|vote| vote.0 > TagVote::MAX_COUNT)
+                    || shared
+                        .reactions
+                        .values()
+                        .any(|vote| vote.0 > ReactionVote::MAX_COUNT)
+                {
+                    return Err(ValidationError::VoteOverflow(parent()));
+                }
+
+                // A tag vote has three choices, so the fourth state is never
+                // advanced to.
+                if shared.tags.values().any(|vote| vote.0 % 4 == 3) {
+                    return Err(ValidationError::InvalidVote(parent()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drop every version of content less than `stable_before` which is
+    /// superseded by a later version, along with its content type. The latest
+    /// version and redacted versions are always kept, so the current content
+    /// is unchanged. Shared state which is bottom, and so records nothing, is
+    /// dropped too.
+    ///
+    /// This is only safe once every version less than `stable_before` is
+    /// causally stable, i.e. every replica has seen it and no concurrent edit
+    /// can still arrive, since joining a slice which still has a dropped
+    /// version brings it back.
+    pub fn compact(&mut self, stable_before: u64) {
+        for (_, owned) in self.owned.iter_mut() {
+            let latest = match owned.content.last_key_value() {
+                Some((version, _)) => *version,
+                None => continue,
+            };
+
+            owned.content.retain(|version, content| {
+                *version >= stable_before || *version == latest || content.is_redacted()
+            });
+            if let Some(content_types) = &mut owned.content_types {
+                content_types.retain(|version, _| owned.content.contains_key(version));
+            }
+        }
+
+        for (_, shared) in self.shared.iter_mut() {
+            shared.prune_bottom();
+        }
+        self.shared.prune_bottom();
+    }
+
+    /// The number of messages this actor has written, including redacted ones.
+    pub fn message_count(&self) -> usize {
+        self.owned.len()
+    }
+
+    /// The size of this slice in bytes, as it would be written to git by
+    /// `Root::save_actor_slice_to_git`, e.g. to check it against a quota
+    /// beforehand. The slice is not buffered to measure it.
+    pub fn encoded_len(&self) -> usize {
+        schema::encoded_len(self).expect("Slices are always valid CBOR")
+    }
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Semilattice, minicbor::Encode, minicbor::Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Root {
     #[n(0)]
     pub inner: MapLattice<ActorID, Slice>,
 }
 
+/// Bounds on what an `Actor` writes, so that its slice stays small. `None`
+/// is unbounded, which is the default. The methods of `Actor` which write
+/// messages or tags fail rather than exceed them; slices written by other
+/// actors are not bound by them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The greatest length of any version of a message's content, in bytes.
+    pub max_content_bytes: Option<usize>,
+    /// The greatest number of tags which this actor has applied to any one
+    /// message.
+    pub max_tags: Option<usize>,
+    /// The greatest number of messages which this actor has written.
+    pub max_messages: Option<usize>,
+}
+
 #[derive(Debug)]
 pub struct Actor<'a> {
     pub id: ActorID,
+    pub device: DeviceID,
     pub slice: &'a mut Slice,
+    /// The current time, e.g. in milliseconds since the Unix epoch, recorded
+    /// as the creation time of new messages.
+    pub clock: Option<fn() -> u64>,
+    /// The limits on what this actor writes.
+    pub limits: Limits,
+    /// The counter of the next message ID, which only ever increases, so that
+    /// no ID is allocated twice even if messages are removed from the slice.
+    next_seq: u64,
 }
 
-impl Actor<'_> {
-    pub fn new(slice: &mut Slice, id: ActorID) -> Actor {
-        Actor { id, slice }
+impl<'a> Actor<'a> {
+    pub fn new(slice: &'a mut Slice, id: ActorID, device: DeviceID) -> Actor<'a> {
+        Actor {
+            next_seq: next_key(slice.owned.keys(), device) >> 16,
+            id,
+            device,
+            slice,
+            clock: None,
+            limits: Limits::default(),
+        }
+    }
+
+    pub fn with_limits(self, limits: Limits) -> Self {
+        Actor { limits, ..self }
+    }
+
+    pub fn with_clock(self, clock: fn() -> u64) -> Self {
+        Actor {
+            clock: Some(clock),
+            ..self
+        }
+    }
+
+    /// Join this actor's slice from `root`, e.g. as written by their other
+    /// devices, into the working slice. IDs allocated afterwards follow every
+    /// ID of this device in either.
+    pub fn observe(&mut self, root: &Root) {
+        if let Some(slice) = root.slice(&self.id) {
+            self.slice.join_assign(slice.clone());
+        }
+
+        let next_seq = next_key(self.slice.owned.keys(), self.device) >> 16;
+        self.next_seq = self.next_seq.max(next_seq);
+    }
+
+    /// Allocate the next unused ID for this device. Devices of the same actor
+    /// share the slice once joined, but never allocate the same ID.
+    fn next_id(&mut self) -> u64 {
+        let id = (self.next_seq << 16) | self.device as u64;
+        self.next_seq += 1;
+        id
+    }
+
+    /// Set the name which this actor is displayed as, superseding any name
+    /// set before on any of their devices.
+    pub fn set_display_name(&mut self, name: String) {
+        let timestamp = self
+            .slice
+            .display_name
+            .as_ref()
+            .map_or(0, |name| name.timestamp + 1);
+
+        self.slice
+            .display_name
+            .join_assign(Some(LWWRegister::new(timestamp, self.device, name)));
     }
 
     pub fn new_thread(
         &mut self,
         title: String,
         message: String,
-        tags: impl IntoIterator<Item = String>,
-    ) -> MessageID {
-        let id = self.slice.owned.len() as u64;
+        tags: impl IntoIterator<Item = Tag>,
+    ) -> Result<MessageID, ActorError> {
+        self.check_new_message(&message)?;
+        let tags: BTreeSet<_> = tags.into_iter().collect();
+        self.check_tags(tags.len())?;
+        let id = self.next_id();
 
-        self.slice.owned.push(Owned {
-            titles: VecLattice::singleton(SetLattice::singleton(title)),
-            content: VecLattice::singleton(Redactable::Data(message)),
-            commits: VecLattice::default(),
-        });
+        self.slice.owned.insert(
+            id,
+            Owned {
+                titles: VecLattice::singleton(SetLattice::singleton(title)),
+                content: MapLattice::singleton(self.device as u64, Redactable::Data(message)),
+                commits: VecLattice::default(),
+                hidden: None,
+                created_at: self.clock.map(|clock| Max(clock())),
+                redaction_reasons: None,
+                attachments: None,
+                content_types: None,
+                quotes: None,
+                redactions: None,
+                edited_at: None,
+            },
+        );
 
         self.slice
             .shared
             .entry_mut(&self.id)
             .entry_mut(&id)
             .tags
-            .join_assign(
-                tags.into_iter()
-                    .map(|x| (x, Max(1)))
-                    .collect::<Vec<_>>()
-                    .into(),
-            );
+            .extend(tags.into_iter().map(|x| (x, Max(1))));
 
-        (self.id.clone(), id)
+        Ok((self.id.clone(), id))
     }
 
-    pub fn reply(&mut self, parent: MessageID, message: String) -> MessageID {
-        let id = self.slice.owned.len() as u64;
+    pub fn reply(&mut self, parent: MessageID, message: String) -> Result<MessageID, ActorError> {
+        self.reply_to_many([parent], message)
+    }
 
-        self.slice.owned.push(Owned {
-            titles: Default::default(),
-            content: VecLattice::singleton(Redactable::Data(message)),
-            commits: Default::default(),
-        });
+    /// Reply to each of `parents` with the same message, such that it appears
+    /// among the replies to each of them.
+    pub fn reply_to_many(
+        &mut self,
+        parents: impl IntoIterator<Item = MessageID>,
+        message: String,
+    ) -> Result<MessageID, ActorError> {
+        self.check_new_message(&message)?;
+        let id = self.next_id();
+
+        self.slice.owned.insert(
+            id,
+            Owned {
+                titles: Default::default(),
+                content: MapLattice::singleton(self.device as u64, Redactable::Data(message)),
+                commits: Default::default(),
+                hidden: None,
+                created_at: self.clock.map(|clock| Max(clock())),
+                redaction_reasons: None,
+                attachments: None,
+                content_types: None,
+                quotes: None,
+                redactions: None,
+                edited_at: None,
+            },
+        );
 
+        for parent in parents {
+            self.slice
+                .shared
+                .entry_mut(&parent.0)
+                .entry_mut(&parent.1)
+                .responses
+                .insert(id);
+        }
+
+        Ok((self.id.clone(), id))
+    }
+
+    /// Like `reply`, but also quote a version of the parent, or a range of
+    /// characters of it.
+    pub fn reply_quoting(
+        &mut self,
+        parent: MessageID,
+        version: u64,
+        range: Option<(u32, u32)>,
+        message: String,
+    ) -> Result<MessageID, ActorError> {
+        let id = self.reply(parent.clone(), message)?;
+        let quote = Quote {
+            message: parent,
+            version,
+            range,
+        };
         self.slice
-            .shared
-            .entry_mut(&parent.0)
-            .entry_mut(&parent.1)
-            .responses
-            .insert(id);
+            .owned
+            .entry_mut(&id.1)
+            .quotes
+            .get_or_insert_with(SetLattice::default)
+            .insert(quote);
+
+        Ok(id)
+    }
+
+    /// Like `reply`, but fails if `parent` is neither one of this actor's
+    /// messages nor materialized in `view`.
+    pub fn try_reply(
+        &mut self,
+        view: &Detailed,
+        parent: MessageID,
+        message: String,
+    ) -> Result<MessageID, ActorError> {
+        let own = parent.0 == self.id
+            && self
+                .slice
+                .owned
+                .get(&parent.1)
+                .is_some_and(|owned| !owned.content.is_empty());
+
+        if !own && !view.contains(&parent) {
+            return Err(ActorError::UnknownParent(parent));
+        }
+
+        self.reply(parent, message)
+    }
+
+    fn check_content(&self, message: &str) -> Result<(), ActorError> {
+        match self.limits.max_content_bytes {
+            Some(max) if message.len() > max => Err(ActorError::ContentTooLong(message.len())),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_new_message(&self, message: &str) -> Result<(), ActorError> {
+        self.check_content(message)?;
+        match self.limits.max_messages {
+            Some(max) if self.slice.message_count() >= max => Err(ActorError::TooManyMessages),
+            _ => Ok(()),
+        }
+    }
 
-        (self.id.clone(), id)
+    fn check_tags(&self, count: usize) -> Result<(), ActorError> {
+        match self.limits.max_tags {
+            Some(max) if count > max => Err(ActorError::TooManyTags(count)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a version of the content of one of this actor's messages, with the
+    /// same content type as the latest version.
+    pub fn edit(&mut self, id: u64, message: String) -> Result<u64, ActorError> {
+        self.own_message(id)?;
+        self.check_content(&message)?;
+        let owned = self.slice.owned.entry_mut(&id);
+        let latest = owned.content.last_key_value().map(|(version, _)| *version);
+        let version = latest.map_or(0, |version| (version >> 16) + 1);
+        let version = (version << 16) | self.device as u64;
+
+        owned.content.insert(version, Redactable::Data(message));
+        if let Some(clock) = self.clock {
+            owned.edited_at.join_assign(Some(Max(clock())));
+        }
+
+        let content_type = latest
+            .and_then(|latest| owned.content_types.as_ref()?.get(&latest))
+            .map(|content_type| content_type.value.clone());
+        if let Some(content_type) = content_type {
+            self.set_content_type(id, version, content_type)?;
+        }
+
+        Ok(version)
+    }
+
+    /// Set the content type of a version of one of this actor's messages.
+    pub fn set_content_type(
+        &mut self,
+        id: u64,
+        version: u64,
+        content_type: String,
+    ) -> Result<(), ActorError> {
+        self.own_message(id)?;
+        let stored = self
+            .slice
+            .owned
+            .entry_mut(&id)
+            .content_types
+            .get_or_insert_with(MapLattice::default)
+            .entry_mut(&version);
+
+        let timestamp = stored.timestamp + 1;
+        stored.join_assign(LWWRegister::new(timestamp, self.device, content_type));
+
+        Ok(())
     }
 
-    pub fn edit(&mut self, id: u64, message: String) -> u64 {
-        let content = &mut self.slice.owned.entry_mut(id).content;
-        let version = content.len() as u64;
+    /// Redact a version of one of this actor's messages, which need not have
+    /// been seen by this device. Other actors' messages cannot be redacted.
+    /// If this actor has a clock, the redaction is recorded as per
+    /// `redact_at`.
+    pub fn redact(&mut self, id: MessageID, version: u64) -> Result<(), ActorError> {
+        match self.clock {
+            Some(clock) => self.redact_at(id, version, clock()),
+            None => self.redact_unrecorded(id, version),
+        }
+    }
 
-        content.push(Redactable::Data(message));
+    /// Like `redact`, but records that this actor redacted the version at the
+    /// given time. Concurrent redactions of the same version are all kept.
+    pub fn redact_at(&mut self, id: MessageID, version: u64, at: u64) -> Result<(), ActorError> {
+        let key = id.1;
+        self.redact_unrecorded(id, version)?;
+
+        let redaction = Redaction {
+            by: self.id.clone(),
+            at,
+        };
+        self.slice
+            .owned
+            .entry_mut(&key)
+            .redactions
+            .get_or_insert_with(MapLattice::default)
+            .entry_mut(&version)
+            .insert(redaction);
 
-        version
+        Ok(())
     }
 
-    pub fn redact(&mut self, id: u64, version: u64) {
+    fn redact_unrecorded(&mut self, id: MessageID, version: u64) -> Result<(), ActorError> {
+        if id.0 != self.id {
+            return Err(ActorError::NotOwned(id));
+        }
+
         self.slice
             .owned
-            .entry_mut(id)
+            .entry_mut(&id.1)
             .content
-            .entry_mut(version)
+            .entry_mut(&version)
             .join_assign(Redactable::Redacted);
+
+        Ok(())
     }
 
-    pub fn react(&mut self, id: MessageID, reaction: Reaction, vote: bool) {
-        let stored_vote = self
+    /// Attach a file to one of this actor's messages, returning the key of
+    /// the attachment.
+    pub fn attach(
+        &mut self,
+        id: u64,
+        content_type: String,
+        blob_oid: String,
+    ) -> Result<u64, ActorError> {
+        self.own_message(id)?;
+        let attachments = self
+            .slice
+            .owned
+            .entry_mut(&id)
+            .attachments
+            .get_or_insert_with(MapLattice::default);
+        let key = next_key(attachments.keys(), self.device);
+
+        attachments.insert(
+            key,
+            Redactable::Data(Attachment {
+                content_type,
+                blob_oid,
+            }),
+        );
+
+        Ok(key)
+    }
+
+    /// Redact an attachment of one of this actor's messages.
+    pub fn redact_attachment(&mut self, id: MessageID, key: u64) -> Result<(), ActorError> {
+        if id.0 != self.id {
+            return Err(ActorError::NotOwned(id));
+        }
+        self.own_message(id.1)?;
+
+        self.slice
+            .owned
+            .entry_mut(&id.1)
+            .attachments
+            .get_or_insert_with(MapLattice::default)
+            .insert(key, Redactable::Redacted);
+
+        Ok(())
+    }
+
+    /// Set the weight of a tag on a message, superseding this actor's previous
+    /// weight for it. This is independent of the votes of `adjust_tags`.
+    pub fn set_tag_weight(&mut self, id: MessageID, tag: Tag, weight: u8) {
+        let stored = self
             .slice
             .shared
             .entry_mut(&id.0)
             .entry_mut(&id.1)
-            .reactions
-            .entry_mut(&reaction);
+            .tag_weights
+            .get_or_insert_with(MapLattice::default)
+            .entry_mut(&tag);
 
-        if stored_vote.0 % 2 != vote as u64 {
-            stored_vote.0 += 1;
-        }
+        stored.bump(Max(weight));
     }
 
-    pub fn adjust_tags(
+    /// Pin or unpin a message within its thread, at a logical time after
+    /// every other pin or unpin by this actor. Of every actor's, the latest
+    /// wins, so an unpin supersedes another actor's pin only once this actor's
+    /// clock has passed the pin's.
+    pub fn set_pinned(&mut self, id: MessageID, pinned: bool) {
+        let clock = self
+            .slice
+            .shared
+            .values()
+            .flat_map(|shared| shared.values())
+            .filter_map(|shared| shared.pinned)
+            .max()
+            .map_or(0, |clock| clock.0 + 1);
+
+        let mut stored = Max(clock);
+        Vote::<2>::advance(&mut stored, pinned as u64);
+
+        self.slice.shared.entry_mut(&id.0).entry_mut(&id.1).pinned = Some(stored);
+    }
+
+    /// Merge the thread rooted at `source_root` into that rooted at
+    /// `target_root`, such that it is listed as a reply to the target rather
+    /// than as a thread of its own. The merge supersedes every earlier one by
+    /// this actor, so merging a thread into itself undoes a merge.
+    pub fn merge_thread_into(&mut self, source_root: MessageID, target_root: MessageID) {
+        let timestamp = self
+            .slice
+            .shared
+            .values()
+            .flat_map(|shared| shared.values())
+            .filter_map(|shared| shared.merged_into.as_ref())
+            .map(|redirect| redirect.timestamp + 1)
+            .max()
+            .unwrap_or(0);
+
+        self.slice
+            .shared
+            .entry_mut(&source_root.0)
+            .entry_mut(&source_root.1)
+            .merged_into
+            .join_assign(Some(LWWRegister::new(
+                timestamp,
+                self.id.clone(),
+                target_root,
+            )));
+    }
+
+    /// Like `redact`, but records why. Concurrent redactions of the same
+    /// version keep every reason.
+    pub fn redact_with_reason(
         &mut self,
         id: MessageID,
-        add: impl IntoIterator<Item = Reaction>,
-        remove: impl IntoIterator<Item = Reaction>,
-    ) {
-        let tags = &mut self.slice.shared.entry_mut(&id.0).entry_mut(&id.1).tags;
+        version: u64,
+        reason: String,
+    ) -> Result<(), ActorError> {
+        let key = id.1;
+        self.redact(id, version)?;
+        self.slice
+            .owned
+            .entry_mut(&key)
+            .redaction_reasons
+            .get_or_insert_with(MapLattice::default)
+            .insert(version, SetLattice::singleton(reason));
 
-        for tag in add {
-            let vote = tags.entry_mut(&tag);
-            // 0 = neutral, 1 = positive, 2 = negative, 3 = invalid
-            match vote.0 % 4 {
-                0 => vote.0 += 1,
-                1 => (),
-                2 => vote.0 += 3,
-                _ => vote.0 += 2,
-            }
-        }
+        Ok(())
+    }
 
-        for tag in remove {
-            let vote = tags.entry_mut(&tag);
-            match vote.0 % 4 {
-                0 => vote.0 += 2,
-                1 => vote.0 += 1,
-                2 => (),
-                _ => vote.0 += 3,
-            }
+    /// Rename a thread which this actor started. The title supersedes every
+    /// title this device has seen, whereas concurrent renames are all kept.
+    /// Replies cannot be given a title, since it would make them threads.
+    pub fn retitle(&mut self, id: u64, title: String) -> Result<(), ActorError> {
+        if self.own_message(id)?.titles.is_empty() {
+            return Err(ActorError::NotAThread((self.id.clone(), id)));
         }
-    }
-}
+        self.slice
+            .owned
+            .entry_mut(&id)
+            .titles
+            .push(SetLattice::singleton(title));
 
-impl Root {
-    pub fn save_actor_slice_to_git(&self, repo: &git2::Repository, actor_name: &str) {
-        let mut buffer = Vec::new();
+        Ok(())
+    }
 
-        minicbor::encode(self.inner.entry(actor_name), &mut buffer)
-            .expect("Failed to CBOR encode actor slice.");
+    /// Hide or reveal a thread which this actor started, or one of their
+    /// replies, along with its replies. Of concurrent changes, the one with
+    /// the greater count wins.
+    pub fn set_thread_hidden(&mut self, id: u64, hidden: bool) -> Result<(), ActorError> {
+        self.own_message(id)?;
+        let vote = self
+            .slice
+            .owned
+            .entry_mut(&id)
+            .hidden
+            .get_or_insert_with(Max::default);
 
-        let threads_tree = repo
-            .find_reference("refs/threads")
-            .and_then(|r| r.peel_to_tree());
+        Vote::<2>::advance(vote, hidden as u64);
 
-        let mut tree = repo
-            .treebuilder(threads_tree.ok().as_ref())
-            .expect("Failed to create tree.");
+        Ok(())
+    }
 
-        tree.insert(
-            &actor_name,
-            repo.blob(&buffer).expect("Failed to record blob."),
-            0o160000,
-        )
-        .expect("Failed to insert blob into tree.");
+    /// The message `id` of this actor, failing unless it has content, so that
+    /// changes to unknown messages do not insert empty ones.
+    fn own_message(&self, id: u64) -> Result<&Owned, ActorError> {
+        self.slice
+            .owned
+            .get(&id)
+            .filter(|owned| !owned.content.is_empty())
+            .ok_or_else(|| ActorError::UnknownMessage((self.id.clone(), id)))
+    }
 
-        let tree_oid = tree.write().expect("Failed to write tree.");
+    /// Set whether the actor has reacted with `reaction`. Repeating the same
+    /// vote leaves the reaction unchanged.
+    pub fn react(&mut self, id: MessageID, reaction: Reaction, vote: bool) {
+        let stored_vote = self
+            .slice
+            .shared
+            .entry_mut(&id.0)
+            .entry_mut(&id.1)
+            .reactions
+            .entry_mut(&reaction);
 
-        repo.reference("refs/threads", tree_oid, true, "log msg")
-            .expect("Failed to update reference");
+        let choice = if vote {
+            ReactionVote::PRESENT
+        } else {
+            ReactionVote::ABSENT
+        };
+        ReactionVote::advance(stored_vote, choice);
     }
 
-    // Can panic; but the panics are occur on their own threads as an
-    // implementation detail of git2...
-    pub fn coalate_slices_into_root_from_git(repo: &git2::Repository) -> Root {
-        let mut root = Root::default();
-
-        let threads_tree = repo
-            .find_reference("refs/threads")
-            .and_then(|r| r.peel_to_tree());
+    /// Set this actor's weight of a reaction to a message, superseding its
+    /// previous weight, unlike the presence of the reaction as per `react`.
+    /// Zero withdraws the reaction.
+    pub fn react_weighted(&mut self, id: MessageID, reaction: Reaction, weight: u64) {
+        let stored = self
+            .slice
+            .shared
+            .entry_mut(&id.0)
+            .entry_mut(&id.1)
+            .reaction_weights
+            .get_or_insert_with(MapLattice::default)
+            .entry_mut(&reaction);
 
-        // Import each writer's slice.
-        if let Ok(ref tree) = threads_tree {
-            tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
-                let actor = entry.name().expect("Invalid reference name").to_owned();
-                root.inner.entry_mut(&actor).join_assign(
-                    minicbor::decode(
-                        entry
-                            .to_object(repo)
-                            .expect("Failed to lookup blob")
-                            .peel_to_blob()
-                            .expect("Expected blob!")
-                            .content(),
-                    )
-                    .expect("Invalid CBOR"),
-                );
-                git2::TreeWalkResult::Ok
-            })
-            .expect("Failed to walk tree.");
-        }
+        stored.bump(Max(weight));
+    }
 
-        root
+    /// The reactions which this actor currently has set on a message.
+    pub fn reactions(&self, id: MessageID) -> impl Iterator<Item = &Reaction> {
+        self.slice
+            .shared
+            .get(&id.0)
+            .and_then(|shared| shared.get(&id.1))
+            .into_iter()
+            .flat_map(|shared| shared.reactions.iter())
+            .filter(|(_, vote)| vote.0 % 2 == ReactionVote::PRESENT)
+            .map(|(reaction, _)| reaction)
     }
 
-    /// Panics if the cache reference does not exist, does not point to a blob,
-    /// or the blob cannot be read or decoded.
-    pub fn load_cache_from_git(repo: &git2::Repository) -> Root {
-        if let Ok(r) = repo
-            .find_reference("refs/threads-materialized")
-            .map(|r| r.peel_to_blob().expect("Expected blob"))
+    /// Retract each of this actor's reactions to a message.
+    pub fn clear_reactions(&mut self, id: MessageID) {
+        let shared = self
+            .slice
+            .shared
+            .get_mut(&id.0)
+            .and_then(|shared| shared.get_mut(&id.1));
+
+        for (_, vote) in shared
+            .into_iter()
+            .flat_map(|shared| shared.reactions.iter_mut())
         {
-            Root {
-                inner: minicbor::decode(r.content()).expect("Failed to decode"),
-            }
-        } else {
-            Root::default()
+            ReactionVote::advance(vote, ReactionVote::ABSENT);
         }
     }
 
-    pub fn save_cache_to_git(&self, repo: &git2::Repository) {
-        let mut buffer = Vec::new();
+    pub fn adjust_tags(
+        &mut self,
+        id: MessageID,
+        add: impl IntoIterator<Item = Tag>,
+        remove: impl IntoIterator<Item = Tag>,
+    ) -> Result<(), ActorError> {
+        let add: Vec<_> = add.into_iter().collect();
+        let remove: Vec<_> = remove.into_iter().collect();
+
+        let mut applied: BTreeSet<_> = self
+            .slice
+            .shared
+            .get(&id.0)
+            .and_then(|shared| shared.get(&id.1))
+            .into_iter()
+            .flat_map(|shared| shared.tags.iter())
+            .filter(|(_, vote)| vote.0 % 4 == TagVote::POSITIVE)
+            .map(|(tag, _)| tag)
+            .collect();
+        applied.extend(&add);
+        for tag in &remove {
+            applied.remove(tag);
+        }
+        self.check_tags(applied.len())?;
 
-        minicbor::encode(&self.inner, &mut buffer).expect("Failed to CBOR encode root.");
+        let tags = &mut self.slice.shared.entry_mut(&id.0).entry_mut(&id.1).tags;
 
-        repo.reference(
-            "refs/threads-materialized",
-            repo.blob(&buffer).expect("Failed to write blob"),
-            true,
-            "log msg",
-        )
-        .expect("Failed to update reference");
-    }
+        for tag in add {
+            TagVote::advance(tags.entry_mut(&tag), TagVote::POSITIVE);
+        }
+
+        for tag in remove {
+            TagVote::advance(tags.entry_mut(&tag), TagVote::NEGATIVE);
+        }
+
+        Ok(())
+    }
+
+    /// Stage changes to apply together with `Transaction::commit`.
+    pub fn transaction(&mut self) -> Transaction<'_, 'a> {
+        Transaction {
+            actor: self,
+            ops: Vec::new(),
+        }
+    }
+}
+
+/// Changes staged by `Actor::transaction`, none of which are applied unless
+/// the transaction is committed. The messages created by a transaction have
+/// consecutive IDs, which are known as soon as they are staged, so that later
+/// changes in the same transaction can refer to them.
+#[derive(Debug)]
+pub struct Transaction<'t, 'a> {
+    actor: &'t mut Actor<'a>,
+    ops: Vec<Op>,
+}
+
+#[derive(Debug)]
+enum Op {
+    NewThread {
+        title: String,
+        message: String,
+        tags: Vec<Tag>,
+    },
+    Reply {
+        parents: Vec<MessageID>,
+        message: String,
+    },
+    Retitle {
+        id: u64,
+        title: String,
+    },
+    AdjustTags {
+        id: MessageID,
+        add: Vec<Tag>,
+        remove: Vec<Tag>,
+    },
+}
+
+impl Transaction<'_, '_> {
+    /// The ID which the next message created by this transaction will have.
+    fn next_id(&self) -> MessageID {
+        let created = self
+            .ops
+            .iter()
+            .filter(|op| matches!(op, Op::NewThread { .. } | Op::Reply { .. }))
+            .count() as u64;
+        let seq = self.actor.next_seq + created;
+
+        (
+            self.actor.id.clone(),
+            (seq << 16) | self.actor.device as u64,
+        )
+    }
+
+    pub fn new_thread(
+        &mut self,
+        title: String,
+        message: String,
+        tags: impl IntoIterator<Item = Tag>,
+    ) -> MessageID {
+        let id = self.next_id();
+        self.ops.push(Op::NewThread {
+            title,
+            message,
+            tags: tags.into_iter().collect(),
+        });
+        id
+    }
+
+    pub fn reply_to_many(
+        &mut self,
+        parents: impl IntoIterator<Item = MessageID>,
+        message: String,
+    ) -> MessageID {
+        let id = self.next_id();
+        self.ops.push(Op::Reply {
+            parents: parents.into_iter().collect(),
+            message,
+        });
+        id
+    }
+
+    pub fn reply(&mut self, parent: MessageID, message: String) -> MessageID {
+        self.reply_to_many([parent], message)
+    }
+
+    pub fn retitle(&mut self, id: u64, title: String) {
+        self.ops.push(Op::Retitle { id, title });
+    }
+
+    pub fn adjust_tags(
+        &mut self,
+        id: MessageID,
+        add: impl IntoIterator<Item = Tag>,
+        remove: impl IntoIterator<Item = Tag>,
+    ) {
+        self.ops.push(Op::AdjustTags {
+            id,
+            add: add.into_iter().collect(),
+            remove: remove.into_iter().collect(),
+        });
+    }
+
+    /// Apply the staged changes in order, returning the IDs of the messages
+    /// which were created. Should any change fail, none are applied.
+    pub fn commit(self) -> Result<Vec<MessageID>, ActorError> {
+        let slice = self.actor.slice.clone();
+        let next_seq = self.actor.next_seq;
+
+        let applied = Self::apply(self.actor, self.ops);
+        if applied.is_err() {
+            *self.actor.slice = slice;
+            self.actor.next_seq = next_seq;
+        }
+
+        applied
+    }
+
+    fn apply(actor: &mut Actor<'_>, ops: Vec<Op>) -> Result<Vec<MessageID>, ActorError> {
+        let mut created = Vec::new();
+
+        for op in ops {
+            match op {
+                Op::NewThread {
+                    title,
+                    message,
+                    tags,
+                } => created.push(actor.new_thread(title, message, tags)?),
+                Op::Reply { parents, message } => {
+                    created.push(actor.reply_to_many(parents, message)?)
+                }
+                Op::Retitle { id, title } => actor.retitle(id, title)?,
+                Op::AdjustTags { id, add, remove } => actor.adjust_tags(id, add, remove)?,
+            }
+        }
+
+        Ok(created)
+    }
+}
+
+/// The key after every key of `device` in `keys`, being a counter in the high
+/// bits and the device in the low 16 bits.
+fn next_key<'k>(keys: impl Iterator<Item = &'k u64>, device: DeviceID) -> u64 {
+    let counter = keys
+        .filter(|key| **key as DeviceID == device)
+        .map(|key| (key >> 16) + 1)
+        .max()
+        .unwrap_or(0);
+
+    (counter << 16) | device as u64
+}
+
+/// The reference to the tree of every actor's slice, by default.
+pub const THREADS_REF: &str = "refs/threads";
+/// The reference to the materialized root, by default.
+pub const CACHE_REF: &str = "refs/threads-materialized";
+/// The reference to the materialized `Detailed` view, by default.
+pub const DETAILED_REF: &str = "refs/threads-detailed";
+
+/// How to record a commit of an actor's slice.
+pub struct CommitOptions<'a> {
+    pub author: git2::Signature<'a>,
+    pub committer: git2::Signature<'a>,
+    pub message: &'a str,
+    /// Sign the commit's content, e.g. with GPG or SSH, returning the
+    /// armored signature to store in the `gpgsig` header.
+    #[allow(clippy::type_complexity)]
+    pub sign: Option<&'a dyn Fn(&str) -> Result<String, git2::Error>>,
+}
+
+/// Look up a reference, treating a missing reference as absent rather than as
+/// an error.
+fn find_reference<'r>(
+    repo: &'r git2::Repository,
+    name: &str,
+) -> Result<Option<git2::Reference<'r>>, ThreadsError> {
+    match repo.find_reference(name) {
+        Ok(r) => Ok(Some(r)),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Call `func` with the name, blob ID, and content of each actor's slice in
+/// the tree at `refname`, if any.
+fn walk_slices(
+    repo: &git2::Repository,
+    refname: &str,
+    mut func: impl FnMut(&str, git2::Oid, &[u8]) -> Result<(), ThreadsError>,
+) -> Result<(), ThreadsError> {
+    let tree = match find_reference(repo, refname)? {
+        Some(r) => r.peel_to_tree()?,
+        None => return Ok(()),
+    };
+
+    // The walk can only be aborted, so keep the error which aborted it.
+    let mut error = None;
+
+    let walked = tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+        let mut visit = || -> Result<(), ThreadsError> {
+            let actor = entry.name().ok_or(ThreadsError::InvalidActorName)?;
+            let blob = entry.to_object(repo)?.peel_to_blob()?;
+            event!(DEBUG, actor, bytes = blob.size(), "read slice");
+            func(actor, blob.id(), blob.content())
+        };
+
+        match visit() {
+            Ok(()) => git2::TreeWalkResult::Ok,
+            Err(err) => {
+                event!(WARN, entry = ?entry.name(), error = %err, "failed to read slice");
+                error = Some(err);
+                git2::TreeWalkResult::Abort
+            }
+        }
+    });
+
+    if let Some(err) = error {
+        return Err(err);
+    }
+    walked?;
+
+    Ok(())
+}
+
+/// Decode `actor`'s slice from the content of its blob, failing if it does not
+/// `validate` when asked to.
+fn decode_slice_of(actor: &str, content: &[u8], validate: bool) -> Result<Slice, ThreadsError> {
+    let slice: Slice = schema::decode(content)?;
+    if validate {
+        slice
+            .validate()
+            .map_err(|err| ThreadsError::Invalid(actor.to_owned(), err))?;
+    }
+
+    Ok(slice)
+}
+
+impl Root {
+    /// Join every actor's slice in `other` into this root.
+    pub fn merge(&mut self, other: Root) {
+        self.join_assign(other);
+    }
+
+    /// Like `merge`, but by value.
+    pub fn merged(mut self, other: Root) -> Root {
+        self.merge(other);
+        self
+    }
+
+    /// The actors with a slice in this root, in order.
+    pub fn actors(&self) -> impl Iterator<Item = &ActorID> {
+        self.inner.keys()
+    }
+
+    pub fn slice(&self, actor: &str) -> Option<&Slice> {
+        self.inner.get(actor)
+    }
+
+    /// A summary of the size of this root, for diagnostics.
+    pub fn stats(&self) -> RootStats {
+        let mut stats = RootStats {
+            actors: self.inner.len(),
+            ..RootStats::default()
+        };
+
+        for slice in self.inner.values() {
+            for owned in slice.owned.values() {
+                stats.messages += 1;
+                if !owned.titles.is_empty() {
+                    stats.threads += 1;
+                }
+                if owned
+                    .content
+                    .last_key_value()
+                    .is_some_and(|(_, content)| content.is_redacted())
+                {
+                    stats.redacted_messages += 1;
+                }
+            }
+
+            stats.tags += slice
+                .shared
+                .values()
+                .flat_map(|shared| shared.values())
+                .flat_map(|shared| shared.tags.values())
+                .filter(|vote| vote.0 % 4 == TagVote::POSITIVE)
+                .count();
+
+            stats.encoded_size += slice.encoded_len();
+        }
+
+        stats
+    }
+}
+
+/// A summary of a `Root`, as returned by `Root::stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RootStats {
+    /// The number of actors with a slice.
+    pub actors: usize,
+    pub threads: usize,
+    /// The number of messages, including threads.
+    pub messages: usize,
+    /// The number of messages whose latest version is redacted.
+    pub redacted_messages: usize,
+    /// The number of tags applied, counting each actor's positive vote on a
+    /// tag of a message once.
+    pub tags: usize,
+    /// The total size of the slices in bytes, as written to git.
+    pub encoded_size: usize,
+}
+
+#[cfg(feature = "serde")]
+impl Root {
+    /// Pretty-printed JSON. Fields are in declaration order and map entries in
+    /// key order, so equal roots always produce the same JSON. Redacted
+    /// content is written as `"Redacted"` rather than omitted.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Roots are always valid JSON")
+    }
+
+    pub fn from_json(json: &str) -> Result<Root, ThreadsError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl Root {
+    /// Write the tree at `refname` with the slices of the given actors
+    /// replaced, without updating the reference. Fails if any of the actors
+    /// has no slice, rather than writing an empty one.
+    fn write_slices_tree<'a>(
+        &self,
+        repo: &git2::Repository,
+        refname: &str,
+        actor_names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<git2::Oid, ThreadsError> {
+        let threads_tree = find_reference(repo, refname)?
+            .map(|r| r.peel_to_tree())
+            .transpose()?;
+
+        let mut tree = repo.treebuilder(threads_tree.as_ref())?;
+        let mut buffer = Vec::new();
+
+        for actor_name in actor_names {
+            let slice = self
+                .inner
+                .get(actor_name)
+                .ok_or_else(|| ThreadsError::UnknownActor(actor_name.to_owned()))?;
+
+            buffer.clear();
+            schema::encode(slice, &mut buffer)?;
+            event!(
+                DEBUG,
+                actor = actor_name,
+                bytes = buffer.len(),
+                "wrote slice"
+            );
+
+            tree.insert(actor_name, repo.blob(&buffer)?, 0o160000)?;
+        }
+
+        Ok(tree.write()?)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, repo), err)
+    )]
+    pub fn save_actor_slice_to_git(
+        &self,
+        repo: &git2::Repository,
+        refname: &str,
+        actor_name: &str,
+    ) -> Result<(), ThreadsError> {
+        let tree_oid = self.write_slices_tree(repo, refname, [actor_name])?;
+
+        repo.reference(refname, tree_oid, true, "log msg")?;
+
+        Ok(())
+    }
+
+    /// Save every actor's slice with a single tree write and reference update.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, repo), err)
+    )]
+    pub fn save_all_to_git(
+        &self,
+        repo: &git2::Repository,
+        refname: &str,
+    ) -> Result<(), ThreadsError> {
+        let tree_oid = self.write_slices_tree(repo, refname, self.actors().map(String::as_str))?;
+
+        repo.reference(refname, tree_oid, true, "log msg")?;
+
+        Ok(())
+    }
+
+    /// Like `save_actor_slice_to_git`, but records the tree in a commit whose
+    /// parent is the commit currently at `refname`, if any. The reference then
+    /// points at the new commit.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, repo, options), err)
+    )]
+    pub fn commit_actor_slice_to_git(
+        &self,
+        repo: &git2::Repository,
+        refname: &str,
+        actor_name: &str,
+        options: &CommitOptions<'_>,
+    ) -> Result<git2::Oid, ThreadsError> {
+        let tree = repo.find_tree(self.write_slices_tree(repo, refname, [actor_name])?)?;
+
+        // A reference previously written by `save_actor_slice_to_git` points
+        // at a bare tree, in which case there is no parent.
+        let parent = find_reference(repo, refname)?.and_then(|r| r.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+
+        let commit_oid = match options.sign {
+            Some(sign) => {
+                let content = repo.commit_create_buffer(
+                    &options.author,
+                    &options.committer,
+                    options.message,
+                    &tree,
+                    &parents,
+                )?;
+                let content = content
+                    .as_str()
+                    .ok_or_else(|| git2::Error::from_str("Commit is not valid UTF-8"))?;
+
+                repo.commit_signed(content, &sign(content)?, None)?
+            }
+            None => repo.commit(
+                None,
+                &options.author,
+                &options.committer,
+                options.message,
+                &tree,
+                &parents,
+            )?,
+        };
+
+        repo.reference(refname, commit_oid, true, options.message)?;
+
+        Ok(commit_oid)
+    }
+
+    /// Decode each actor's slice in turn and pass it to `func`, such that only
+    /// one slice is held in memory at a time.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(repo, func), err)
+    )]
+    pub fn stream_slices_from_git(
+        repo: &git2::Repository,
+        refname: &str,
+        mut func: impl FnMut(ActorID, Slice),
+    ) -> Result<(), ThreadsError> {
+        walk_slices(repo, refname, |actor, _, content| {
+            func(actor.to_owned(), schema::decode(content)?);
+            Ok(())
+        })
+    }
+
+    /// Join every actor's slice in the tree at `refname`. If `validate`, a
+    /// slice which does not `validate` is an error rather than joined.
+    #[cfg(not(feature = "parallel"))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(repo), err)
+    )]
+    pub fn coalate_slices_into_root_from_git(
+        repo: &git2::Repository,
+        refname: &str,
+        validate: bool,
+    ) -> Result<Root, ThreadsError> {
+        let mut root = Root::default();
+
+        // Import each writer's slice.
+        walk_slices(repo, refname, |actor, _, content| {
+            let slice = decode_slice_of(actor, content, validate)?;
+            root.inner.entry_mut(actor).join_assign(slice);
+            Ok(())
+        })?;
+        event!(DEBUG, slices = root.inner.len(), "coalated slices");
+
+        Ok(root)
+    }
+
+    /// Like `coalate_slices_into_root_from_git`, but only if the tree at
+    /// `refname` is not `seen`, the tree from which `root` was previously
+    /// coalated. Both are updated if it changed. Returns whether it changed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(repo, seen, root), err)
+    )]
+    pub fn coalate_slices_into_root_from_git_if_changed(
+        repo: &git2::Repository,
+        refname: &str,
+        validate: bool,
+        seen: &mut Option<git2::Oid>,
+        root: &mut Root,
+    ) -> Result<bool, ThreadsError> {
+        let tree = match find_reference(repo, refname)? {
+            Some(r) => Some(r.peel_to_tree()?.id()),
+            None => None,
+        };
+        if tree == *seen {
+            return Ok(false);
+        }
+
+        // Should the reference move before the slices are read, the newer tree
+        // is coalated but the older one recorded, so it is merely read again.
+        *root = Self::coalate_slices_into_root_from_git(repo, refname, validate)?;
+        *seen = tree;
+
+        Ok(true)
+    }
+
+    /// Join every actor's slice in the tree at `refname`, validating them if
+    /// `validate`. Decodes the slices in parallel. Since the join is
+    /// associative and commutative, the order in which they are joined does
+    /// not matter.
+    #[cfg(feature = "parallel")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(repo), err)
+    )]
+    pub fn coalate_slices_into_root_from_git(
+        repo: &git2::Repository,
+        refname: &str,
+        validate: bool,
+    ) -> Result<Root, ThreadsError> {
+        use rayon::prelude::*;
+
+        // Blobs can't be shared between threads, so copy their content.
+        let mut blobs = Vec::new();
+        walk_slices(repo, refname, |actor, _, content| {
+            blobs.push((actor.to_owned(), content.to_vec()));
+            Ok(())
+        })?;
+
+        let root = blobs
+            .into_par_iter()
+            .map(|(actor, content)| {
+                let slice = decode_slice_of(&actor, &content, validate)?;
+                Ok(Root {
+                    inner: MapLattice::singleton(actor, slice),
+                })
+            })
+            .try_reduce(Root::default, |a, b| Ok::<_, ThreadsError>(a.join(b)))?;
+        event!(DEBUG, slices = root.inner.len(), "coalated slices");
+
+        Ok(root)
+    }
+
+    /// Join into `detailed` only those slices whose blobs it has not already
+    /// materialized. Returns the number of slices which were joined.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(repo, detailed), err)
+    )]
+    pub fn materialize_incremental(
+        repo: &git2::Repository,
+        refname: &str,
+        detailed: &mut Detailed,
+    ) -> Result<usize, ThreadsError> {
+        let mut changed = Root::default();
+        let mut oids = Vec::new();
+
+        walk_slices(repo, refname, |actor, oid, content| {
+            if !detailed.is_materialized(actor, oid.as_bytes()) {
+                changed
+                    .inner
+                    .entry_mut(actor)
+                    .join_assign(schema::decode(content)?);
+                oids.push((actor.to_owned(), oid.as_bytes().to_vec()));
+            }
+            Ok(())
+        })?;
+
+        // Only record the blobs once all of them have been decoded.
+        *detailed = mem::take(detailed).join_root(changed);
+        let count = oids.len();
+        for (actor, oid) in oids {
+            detailed.mark_materialized(actor, oid);
+        }
+        event!(DEBUG, slices = count, "materialized slices");
+
+        Ok(count)
+    }
+
+    /// Load the materialized cache, or an empty root if the cache reference
+    /// does not exist.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(repo), err)
+    )]
+    pub fn load_cache_from_git(
+        repo: &git2::Repository,
+        refname: &str,
+    ) -> Result<Root, ThreadsError> {
+        match find_reference(repo, refname)? {
+            Some(r) => {
+                let blob = r.peel_to_blob()?;
+                event!(DEBUG, bytes = blob.size(), "read cache");
+                Ok(Root {
+                    inner: schema::decode(blob.content())?,
+                })
+            }
+            None => Ok(Root::default()),
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, repo), err)
+    )]
+    pub fn save_cache_to_git(
+        &self,
+        repo: &git2::Repository,
+        refname: &str,
+    ) -> Result<(), ThreadsError> {
+        let mut buffer = Vec::new();
+
+        schema::encode(&self.inner, &mut buffer)?;
+        event!(DEBUG, bytes = buffer.len(), "wrote cache");
+
+        repo.reference(refname, repo.blob(&buffer)?, true, "log msg")?;
+
+        Ok(())
+    }
+
+    /// Like `save_cache_to_git`, but writes neither the blob nor the reference
+    /// if the reference already points at an identical blob. Returns whether
+    /// anything was written.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, repo), err)
+    )]
+    pub fn save_cache_if_changed(
+        &self,
+        repo: &git2::Repository,
+        refname: &str,
+    ) -> Result<bool, ThreadsError> {
+        let mut buffer = Vec::new();
+
+        schema::encode(&self.inner, &mut buffer)?;
+
+        let oid = git2::Oid::hash_object(git2::ObjectType::Blob, &buffer)?;
+        if find_reference(repo, refname)?.and_then(|r| r.target()) == Some(oid) {
+            event!(DEBUG, "cache unchanged");
+            return Ok(false);
+        }
+
+        event!(DEBUG, bytes = buffer.len(), "wrote cache");
+        repo.reference(refname, repo.blob(&buffer)?, true, "log msg")?;
+
+        Ok(true)
+    }
+}
+
+#[test]
+fn devices_never_collide() {
+    let mut laptop = Slice::default();
+    let mut phone = Slice::default();
+
+    let a = Actor::new(&mut laptop, "alice".to_owned(), 0)
+        .new_thread("From my laptop".to_owned(), "Hello world.".to_owned(), [])
+        .unwrap();
+    let b = Actor::new(&mut phone, "alice".to_owned(), 1)
+        .new_thread("From my phone".to_owned(), "Hello kitty.".to_owned(), [])
+        .unwrap();
+    assert_ne!(a, b);
+
+    let mut slice = laptop.join(phone);
+    assert_eq!(slice.owned.len(), 2);
+
+    // Each device continues from its own counter.
+    let c = Actor::new(&mut slice, "alice".to_owned(), 1)
+        .reply(a.clone(), "Hi!".to_owned())
+        .unwrap();
+    assert_eq!(c.1, (1 << 16) | 1);
+
+    for (id, content) in [(a.1, "Hello world."), (b.1, "Hello kitty.")] {
+        assert_eq!(
+            slice.owned.entry(&id).map(|owned| &owned.content[..]),
+            Some(&[(id & 0xffff, Redactable::Data(content.to_owned()))][..])
+        );
+    }
+}
+
+#[test]
+fn concurrent_edits_are_retained() {
+    let mut slice = Slice::default();
+    let id = Actor::new(&mut slice, "alice".to_owned(), 0)
+        .new_thread("Typo".to_owned(), "Hello wrold.".to_owned(), [])
+        .unwrap();
+
+    let mut laptop = slice.clone();
+    let mut phone = slice;
+
+    let a = Actor::new(&mut laptop, "alice".to_owned(), 1)
+        .edit(id.1, "Hello world.".to_owned())
+        .unwrap();
+    let b = Actor::new(&mut phone, "alice".to_owned(), 2)
+        .edit(id.1, "Hello, world.".to_owned())
+        .unwrap();
+    assert_ne!(a, b);
+
+    let slice = laptop.join(phone);
+    assert_eq!(
+        slice.owned.entry(&id.1).map(|owned| &owned.content[..]),
+        Some(
+            &[
+                (0, Redactable::Data("Hello wrold.".to_owned())),
+                (a, Redactable::Data("Hello world.".to_owned())),
+                (b, Redactable::Data("Hello, world.".to_owned())),
+            ][..]
+        )
+    );
+}
+
+#[test]
+fn deltas_converge() {
+    let mut base = Slice::default();
+    let thread = Actor::new(&mut base, "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+
+    let mut laptop = base.clone();
+    let mut alice = Actor::new(&mut laptop, "alice".to_owned(), 0);
+    alice.edit(thread.1, "Hello!".to_owned()).unwrap();
+    alice.reply(thread.clone(), "Anyone?".to_owned()).unwrap();
+    alice
+        .adjust_tags(thread.clone(), ["feature".into()], ["bug".into()])
+        .unwrap();
+
+    let mut phone = base.clone();
+    Actor::new(&mut phone, "alice".to_owned(), 1).react(thread, ":+1:".into(), true);
+
+    for (from, to) in [(&laptop, &phone), (&phone, &laptop), (&laptop, &base)] {
+        let delta = from.delta_since(to);
+        assert_eq!(to.clone().join(delta), to.clone().join(from.clone()));
+    }
+
+    // Nothing is missing from a replica which has seen everything.
+    let joined = laptop.clone().join(phone.clone());
+    assert_eq!(laptop.delta_since(&joined), Slice::default());
+    // Unchanged messages are not resent.
+    assert!(phone.delta_since(&base).owned.is_empty());
+}
+
+#[test]
+fn concurrent_slices() {
+    use semilog::{dominates, is_concurrent};
+
+    let mut base = Slice::default();
+    let thread = Actor::new(&mut base, "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut laptop = base.clone();
+    Actor::new(&mut laptop, "alice".to_owned(), 0)
+        .reply(thread.clone(), "Hi".to_owned())
+        .unwrap();
+    let mut phone = base.clone();
+    Actor::new(&mut phone, "alice".to_owned(), 1).react(thread, ":+1:".into(), true);
+
+    assert!(!is_concurrent(&base, &base.clone()));
+    assert!(!is_concurrent(&base, &laptop));
+    assert!(dominates(&laptop, &base) && !dominates(&base, &laptop));
+    assert!(is_concurrent(&laptop, &phone));
+    assert!(dominates(&laptop.clone().join(phone.clone()), &phone));
+}
+
+#[test]
+fn reactions_can_be_cleared() {
+    let mut base = Slice::default();
+    let id = Actor::new(&mut base, "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut alice = Actor::new(&mut base, "alice".to_owned(), 0);
+    alice.react(id.clone(), ":+1:".into(), true);
+    alice.react(id.clone(), ":tada:".into(), true);
+    alice.react(id.clone(), ":eyes:".into(), true);
+    alice.react(id.clone(), ":eyes:".into(), false);
+    assert_eq!(
+        alice.reactions(id.clone()).collect::<Vec<_>>(),
+        [":+1:", ":tada:"]
+    );
+
+    let mut laptop = base.clone();
+    let mut alice = Actor::new(&mut laptop, "alice".to_owned(), 0);
+    alice.clear_reactions(id.clone());
+    assert_eq!(alice.reactions(id.clone()).count(), 0);
+
+    // A stale replica does not resurrect the cleared reactions.
+    let mut slice = base.join(laptop);
+    let alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    assert_eq!(alice.reactions(id).count(), 0);
+}
+
+#[test]
+fn only_own_messages_are_redacted() {
+    let mut root = Root::default();
+    let id = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    assert_eq!(
+        bob.redact(id.clone(), 0),
+        Err(ActorError::NotOwned(id.clone()))
+    );
+    assert!(bob.slice.owned.is_empty());
+
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    assert_eq!(alice.redact(id.clone(), 0), Ok(()));
+    assert_eq!(
+        alice.slice.owned.get(&id.1).unwrap().content[..],
+        [(0, Redactable::Redacted)]
+    );
+}
+
+#[test]
+fn replies_to_unknown_parents() {
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    // Alice's own messages are known before they are materialized.
+    let view = Detailed::default();
+    assert!(alice
+        .try_reply(&view, thread.clone(), "Hi".to_owned())
+        .is_ok());
+
+    let typo = ("alice".to_owned(), 1);
+    assert_eq!(
+        alice.try_reply(&view, typo.clone(), "Hi".to_owned()),
+        Err(ActorError::UnknownParent(typo))
+    );
+
+    let view = Detailed::default().join_root(root.clone());
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let reply = bob.try_reply(&view, thread, "Hi".to_owned()).unwrap();
+
+    let unseen = ("carol".to_owned(), 0);
+    assert_eq!(
+        bob.try_reply(&view, unseen.clone(), "Hi".to_owned()),
+        Err(ActorError::UnknownParent(unseen))
+    );
+    assert_eq!(bob.slice.owned.keys().collect::<Vec<_>>(), [&reply.1]);
+}
+
+#[test]
+fn ids_are_not_reused() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 3);
+    let a = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let b = alice.reply(a.clone(), "Hi".to_owned()).unwrap();
+    assert_eq!([a.1, b.1], [3, (1 << 16) | 3]);
+
+    alice.slice.owned.retain(|id, _| *id != b.1);
+    let c = alice.reply(a.clone(), "Hi again".to_owned()).unwrap();
+    assert_eq!(c.1, (2 << 16) | 3);
+
+    // A new handle continues after the messages still in the slice.
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 3);
+    assert_eq!(alice.reply(a, "Bye".to_owned()).unwrap().1, (3 << 16) | 3);
+}
+
+#[test]
+fn invalid_slices() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let reply = alice.reply(thread.clone(), "Hi".to_owned()).unwrap();
+    alice.react(thread.clone(), ":+1:".into(), true);
+    assert_eq!(slice.validate(), Ok(()));
+
+    let mut dangling = slice.clone();
+    dangling.owned.retain(|id, _| *id != reply.1);
+    assert_eq!(
+        dangling.validate(),
+        Err(ValidationError::DanglingReply(thread.clone(), reply.1))
+    );
+
+    // Threads as well as replies need content.
+    for id in [thread.1, reply.1] {
+        let mut empty = slice.clone();
+        empty.owned.entry_mut(&id).content = MapLattice::default();
+        assert_eq!(empty.validate(), Err(ValidationError::EmptyContent(id)));
+    }
+
+    let mut invalid = slice.clone();
+    let shared = invalid.shared.entry_mut(&thread.0).entry_mut(&thread.1);
+    shared.tags.insert("bug".into(), Max(3));
+    assert_eq!(
+        invalid.validate(),
+        Err(ValidationError::InvalidVote(thread.clone()))
+    );
+
+    let mut overflow = slice.clone();
+    let shared = overflow.shared.entry_mut(&thread.0).entry_mut(&thread.1);
+    *shared.reactions.entry_mut(&Reaction::from(":+1:")) = Max(u64::MAX);
+    assert_eq!(
+        overflow.validate(),
+        Err(ValidationError::VoteOverflow(thread.clone()))
+    );
+
+    let mut overflow = slice;
+    let shared = overflow.shared.entry_mut(&thread.0).entry_mut(&thread.1);
+    shared.tags.insert("bug".into(), Max(u64::MAX));
+    assert_eq!(
+        overflow.validate(),
+        Err(ValidationError::VoteOverflow(thread))
+    );
+}
+
+#[test]
+fn saturating_votes() {
+    let mut local = Slice::default();
+    let thread = Actor::new(&mut local, "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    // Another device sets the counters to their greatest values.
+    let mut remote = local.clone();
+    let shared = remote.shared.entry_mut(&thread.0).entry_mut(&thread.1);
+    shared.reactions.insert(":+1:".into(), Max(u64::MAX));
+    shared.tags.insert("bug".into(), Max(u64::MAX));
+
+    let mut slice = local.join(remote);
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    alice.react(thread.clone(), ":+1:".into(), false);
+    alice
+        .adjust_tags(thread.clone(), [], ["bug".into()])
+        .unwrap();
+
+    let shared = alice
+        .slice
+        .shared
+        .get(&thread.0)
+        .unwrap()
+        .get(&thread.1)
+        .unwrap();
+    assert_eq!(shared.reactions.get(":+1:"), Some(&Max(u64::MAX)));
+    assert_eq!(shared.tags.get("bug"), Some(&Max(u64::MAX)));
+}
+
+#[test]
+fn observing_roots() {
+    let mut remote = Root::default();
+    let mut alice = Actor::new(remote.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    // The same device, restored from an older backup.
+    let mut local = Slice::default();
+    let mut alice = Actor::new(&mut local, "alice".to_owned(), 0);
+    Actor::new(remote.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .reply(thread.clone(), "Hi".to_owned())
+        .unwrap();
+    Actor::new(remote.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .reply(thread.clone(), "Hi".to_owned())
+        .unwrap();
+
+    alice.observe(&remote);
+    assert_eq!(alice.slice, remote.inner.get("alice").unwrap());
+    assert_eq!(alice.reply(thread, "Again".to_owned()).unwrap().1, 2 << 16);
+}
+
+#[test]
+fn transactions() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 1);
+    let before = alice
+        .new_thread("Before".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut tx = alice.transaction();
+    let thread = tx.new_thread("Draft".to_owned(), "Hello".to_owned(), ["bug".into()]);
+    tx.retitle(thread.1, "Final".to_owned());
+    let reply = tx.reply(thread.clone(), "Details".to_owned());
+    tx.adjust_tags(thread.clone(), ["ui".into()], ["bug".into()]);
+
+    // Nothing is applied until the transaction is committed.
+    assert_eq!(tx.actor.slice.owned.len(), 1);
+    assert_eq!(tx.commit().unwrap(), [thread.clone(), reply.clone()]);
+    assert_eq!([thread.1, reply.1], [(1 << 16) | 1, (2 << 16) | 1]);
+
+    let detailed = Detailed::default().join_root(Root {
+        inner: MapLattice::singleton("alice".to_owned(), alice.slice.clone()),
+    });
+    assert_eq!(detailed.titles(&thread), ["Final"]);
+    assert_eq!(
+        detailed.threads_page(0, 10, detailed::ThreadSort::Id)[1].top_tags,
+        [(Tag::from("ui"), 1)]
+    );
+    assert_eq!(detailed.thread_stats(&thread).unwrap().reply_count, 1);
+
+    // A transaction which is dropped changes nothing.
+    let mut tx = alice.transaction();
+    tx.reply(before, "Discarded".to_owned());
+    drop(tx);
+    assert_eq!(alice.slice.owned.len(), 3);
+}
+
+#[test]
+fn merging_roots() {
+    let mut alice = Root::default();
+    let thread = Actor::new(alice.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let mut bob = Root::default();
+    Actor::new(bob.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .reply(thread.clone(), "Hi".to_owned())
+        .unwrap();
+
+    // Disjoint roots keep every actor's slice.
+    let mut merged = alice.clone();
+    merged.merge(bob.clone());
+    assert_eq!(merged.inner.keys().collect::<Vec<_>>(), ["alice", "bob"]);
+    assert_eq!(merged.inner.get("bob"), bob.inner.get("bob"));
+
+    // Overlapping roots join the slices of the same actor.
+    let mut laptop = merged.clone();
+    Actor::new(laptop.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .edit(thread.1, "Hello!".to_owned())
+        .unwrap();
+    let mut phone = merged.clone();
+    Actor::new(phone.inner.entry_mut("alice"), "alice".to_owned(), 1).react(
+        thread,
+        ":+1:".into(),
+        true,
+    );
+
+    let merged = laptop.clone().merged(phone.clone());
+    assert_eq!(merged, laptop.join(phone));
+    assert_eq!(
+        merged
+            .inner
+            .get("alice")
+            .unwrap()
+            .owned
+            .get(&0)
+            .unwrap()
+            .content
+            .len(),
+        2
+    );
+    assert_eq!(merged.inner.get("alice").unwrap().shared.len(), 1);
+}
+
+#[test]
+fn enumerating_actors() {
+    let mut root = Root::default();
+    assert_eq!(root.actors().count(), 0);
+
+    let thread = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    for actor in ["carol", "alice"] {
+        Actor::new(root.inner.entry_mut(actor), actor.to_owned(), 0)
+            .reply(thread.clone(), "Hi".to_owned())
+            .unwrap();
+    }
+
+    assert_eq!(root.actors().collect::<Vec<_>>(), ["alice", "bob", "carol"]);
+    for actor in root.actors() {
+        assert_eq!(root.slice(actor).unwrap().owned.len(), 1);
+    }
+    assert!(root.slice("bob").unwrap().owned.get(&thread.1).is_some());
+    assert_eq!(root.slice("dave"), None);
+}
+
+#[test]
+fn compaction() {
+    let mut laptop = Slice::default();
+    let mut alice = Actor::new(&mut laptop, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let redacted = alice.edit(thread.1, "Oops".to_owned()).unwrap();
+    alice.redact(thread.clone(), redacted).unwrap();
+
+    // Concurrent edits from two devices, of which the phone's wins.
+    let mut phone = laptop.clone();
+    let first = Actor::new(&mut laptop, "alice".to_owned(), 0)
+        .edit(thread.1, "Hi".to_owned())
+        .unwrap();
+    let second = Actor::new(&mut phone, "alice".to_owned(), 1)
+        .edit(thread.1, "Hey".to_owned())
+        .unwrap();
+    let mut slice = laptop.join(phone);
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    alice
+        .set_content_type(thread.1, first, "text/markdown".to_owned())
+        .unwrap();
+    alice
+        .set_content_type(thread.1, second, "text/markdown".to_owned())
+        .unwrap();
+    let latest = alice.edit(thread.1, "Hey!".to_owned()).unwrap();
+
+    let versions = |slice: &Slice| {
+        let owned = slice.owned.get(&thread.1).unwrap();
+        let content_types = owned.content_types.iter().flat_map(|x| x.keys());
+        (
+            owned.content.keys().copied().collect::<Vec<_>>(),
+            content_types.copied().collect::<Vec<_>>(),
+        )
+    };
+    let current = |slice: &Slice| {
+        Detailed::default()
+            .join_root(Root {
+                inner: MapLattice::singleton("alice".to_owned(), slice.clone()),
+            })
+            .current_content(&thread)
+            .map(str::to_owned)
+    };
+    assert_eq!(
+        versions(&slice),
+        (
+            vec![0, redacted, first, second, latest],
+            vec![first, second, latest]
+        )
+    );
+
+    // Versions which are not yet stable are kept.
+    let mut compacted = slice.clone();
+    compacted.compact(first);
+    assert_eq!(
+        versions(&compacted),
+        (
+            vec![redacted, first, second, latest],
+            vec![first, second, latest]
+        )
+    );
+
+    compacted.compact(u64::MAX);
+    assert_eq!(versions(&compacted), (vec![redacted, latest], vec![latest]));
+    assert_eq!(current(&compacted), current(&slice));
+    assert_eq!(current(&compacted).as_deref(), Some("Hey!"));
+
+    // Compaction keeps the latest version even if it is redacted.
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    alice.redact(thread.clone(), latest).unwrap();
+    slice.compact(u64::MAX);
+    assert_eq!(versions(&slice), (vec![redacted, latest], vec![latest]));
+    assert_eq!(current(&slice), None);
+}
+
+#[test]
+fn root_stats() {
+    let mut root = Root::default();
+    assert_eq!(root.stats(), RootStats::default());
+
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+    alice
+        .new_thread("Other".to_owned(), "Hi".to_owned(), [])
+        .unwrap();
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    let reply = bob.reply(thread.clone(), "Oops".to_owned()).unwrap();
+    bob.redact(reply, 0).unwrap();
+    bob.adjust_tags(thread.clone(), ["bug".into(), "ui".into()], [])
+        .unwrap();
+    bob.adjust_tags(thread, [], ["ui".into()]).unwrap();
+
+    let stats = root.stats();
+    assert_eq!(
+        stats,
+        RootStats {
+            actors: 2,
+            threads: 2,
+            messages: 3,
+            redacted_messages: 1,
+            tags: 2,
+            encoded_size: stats.encoded_size,
+        }
+    );
+
+    let mut buffer = Vec::new();
+    for slice in root.inner.values() {
+        schema::encode(slice, &mut buffer).unwrap();
+    }
+    assert_eq!(stats.encoded_size, buffer.len());
+}
+
+#[test]
+fn compaction_prunes_shared() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    alice.react(thread.clone(), ":+1:".into(), true);
+
+    // Looking up shared state inserts an empty entry.
+    let mut pruned = slice.clone();
+    pruned.shared.entry_mut("bob").entry_mut(&0);
+    pruned.shared.entry_mut("alice").entry_mut(&(thread.1 + 1));
+    assert_eq!(pruned.shared.get("alice").unwrap().len(), 2);
+
+    pruned.compact(0);
+    assert_eq!(pruned, slice);
+}
+
+#[test]
+fn limits() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0).with_limits(Limits {
+        max_content_bytes: Some(5),
+        max_tags: Some(2),
+        max_messages: Some(2),
+    });
+
+    // Content of exactly the greatest length is allowed.
+    assert_eq!(
+        alice.new_thread("Title".to_owned(), "Hello!".to_owned(), []),
+        Err(ActorError::ContentTooLong(6))
+    );
+    assert_eq!(
+        alice.new_thread(
+            "Title".to_owned(),
+            "Hi".to_owned(),
+            ["a".into(), "b".into(), "c".into()]
+        ),
+        Err(ActorError::TooManyTags(3))
+    );
+    let thread = alice
+        .new_thread(
+            "Title".to_owned(),
+            "Hello".to_owned(),
+            ["a".into(), "a".into()],
+        )
+        .unwrap();
+    assert_eq!(
+        alice.edit(thread.1, "Hello!".to_owned()),
+        Err(ActorError::ContentTooLong(6))
+    );
+    alice.edit(thread.1, "Hey".to_owned()).unwrap();
+
+    // Removed tags make room for others.
+    alice.adjust_tags(thread.clone(), ["b".into()], []).unwrap();
+    assert_eq!(
+        alice.adjust_tags(thread.clone(), ["c".into()], []),
+        Err(ActorError::TooManyTags(3))
+    );
+    alice
+        .adjust_tags(thread.clone(), ["c".into()], ["a".into()])
+        .unwrap();
+
+    assert_eq!(
+        alice.reply_quoting(thread.clone(), 0, None, "Hello!".to_owned()),
+        Err(ActorError::ContentTooLong(6))
+    );
+    alice.reply(thread.clone(), "Hi".to_owned()).unwrap();
+    assert_eq!(
+        alice.reply_to_many([thread.clone()], "Hi".to_owned()),
+        Err(ActorError::TooManyMessages)
+    );
+    assert_eq!(
+        alice.new_thread("Title".to_owned(), "Hi".to_owned(), []),
+        Err(ActorError::TooManyMessages)
+    );
+
+    let mut tx = alice.transaction();
+    tx.adjust_tags(thread.clone(), [], ["b".into()]);
+    tx.reply(thread.clone(), "Hi".to_owned());
+    assert_eq!(tx.commit(), Err(ActorError::TooManyMessages));
+
+    // Failures write nothing.
+    assert_eq!(alice.slice.owned.len(), 2);
+    assert_eq!(alice.slice.owned.get(&thread.1).unwrap().content.len(), 2);
+    let tags = &slice
+        .shared
+        .get("alice")
+        .unwrap()
+        .get(&thread.1)
+        .unwrap()
+        .tags;
+    assert_eq!(
+        tags.iter()
+            .filter(|(_, vote)| vote.0 % 4 == TagVote::POSITIVE)
+            .map(|(tag, _)| tag.0.as_str())
+            .collect::<Vec<_>>(),
+        ["b", "c"]
+    );
+}
+
+#[test]
+fn slice_size() {
+    let mut slice = Slice::default();
+    let encoded_len = |slice: &Slice| {
+        let mut buffer = Vec::new();
+        schema::encode(slice, &mut buffer).unwrap();
+        buffer.len()
+    };
+    assert_eq!(slice.message_count(), 0);
+    assert_eq!(slice.encoded_len(), encoded_len(&slice));
+
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+    let reply = alice.reply(thread.clone(), "x".repeat(1000)).unwrap();
+    alice.redact(reply, 0).unwrap();
+    alice.react(thread, ":+1:".into(), true);
+
+    assert_eq!(slice.message_count(), 2);
+    assert_eq!(slice.encoded_len(), encoded_len(&slice));
+}
+
+#[test]
+fn hiding_unknown_messages() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let reply = alice.reply(thread.clone(), "Hi".to_owned()).unwrap();
+
+    let unknown = reply.1 + (1 << 16);
+    assert_eq!(
+        alice.set_thread_hidden(unknown, true),
+        Err(ActorError::UnknownMessage(("alice".to_owned(), unknown)))
+    );
+    assert_eq!(alice.set_thread_hidden(thread.1, true), Ok(()));
+    assert_eq!(alice.set_thread_hidden(reply.1, true), Ok(()));
+
+    // Failures write nothing.
+    assert_eq!(alice.slice.message_count(), 2);
+}
+
+#[test]
+fn retitling_replies() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let reply = alice.reply(thread.clone(), "Hi".to_owned()).unwrap();
+    let unknown = reply.1 + (1 << 16);
+
+    assert_eq!(
+        alice.retitle(reply.1, "Title".to_owned()),
+        Err(ActorError::NotAThread(reply.clone()))
+    );
+    assert_eq!(
+        alice.retitle(unknown, "Title".to_owned()),
+        Err(ActorError::UnknownMessage(("alice".to_owned(), unknown)))
+    );
+    assert_eq!(alice.retitle(thread.1, "Renamed".to_owned()), Ok(()));
+
+    // Failures write nothing.
+    assert_eq!(alice.slice.message_count(), 2);
+    assert!(alice.slice.owned.get(&reply.1).unwrap().titles.is_empty());
+
+    // Nor does a transaction in which any change fails.
+    let mut tx = alice.transaction();
+    let staged = tx.new_thread("Draft".to_owned(), "Hello".to_owned(), []);
+    tx.retitle(staged.1, "Final".to_owned());
+    tx.retitle(reply.1, "Title".to_owned());
+    assert_eq!(tx.commit(), Err(ActorError::NotAThread(reply)));
+    assert_eq!(alice.slice.message_count(), 2);
+    assert_eq!(
+        alice
+            .new_thread("Draft".to_owned(), "Hello".to_owned(), [])
+            .unwrap(),
+        staged
+    );
+}
+
+#[test]
+fn changing_unknown_messages() {
+    let mut slice = Slice::default();
+    let mut alice = Actor::new(&mut slice, "alice".to_owned(), 0);
+    let thread = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+    let unknown = thread.1 + (1 << 16);
+    let before = alice.slice.clone();
+
+    assert_eq!(
+        alice.attach(unknown, "text/plain".to_owned(), "d670460b".to_owned()),
+        Err(ActorError::UnknownMessage(("alice".to_owned(), unknown)))
+    );
+    assert_eq!(
+        alice.redact_attachment(("alice".to_owned(), unknown), 0),
+        Err(ActorError::UnknownMessage(("alice".to_owned(), unknown)))
+    );
+    assert_eq!(
+        alice.set_content_type(unknown, 0, "text/markdown".to_owned()),
+        Err(ActorError::UnknownMessage(("alice".to_owned(), unknown)))
+    );
+    assert_eq!(
+        alice.edit(unknown, "Hello".to_owned()),
+        Err(ActorError::UnknownMessage(("alice".to_owned(), unknown)))
+    );
+    assert_eq!(*alice.slice, before);
+    assert_eq!(alice.slice.validate(), Ok(()));
 }
@@ -0,0 +1,243 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+
+use semilog::{MapLattice, Redactable, SetLattice, VecLattice};
+
+use crate::detailed::Detailed;
+use crate::{ActorID, Owned, Patchset, Shared, Slice, ThreadsError};
+
+/// The bytes which begin every blob written by this crate, followed by the
+/// schema version as a big-endian `u16` and then the CBOR content.
+pub const MAGIC: &[u8; 4] = b"thrd";
+
+/// The schema version of blobs written by this crate. Blobs written before
+/// the envelope was introduced, which are bare CBOR, are version 0.
+pub const CURRENT_SCHEMA: u16 = 1;
+
+/// Encode `value` into `buffer` within the versioned envelope.
+pub(crate) fn encode<T: minicbor::Encode>(
+    value: &T,
+    buffer: &mut Vec<u8>,
+) -> Result<(), ThreadsError> {
+    buffer.extend_from_slice(MAGIC);
+    buffer.extend_from_slice(&CURRENT_SCHEMA.to_be_bytes());
+    minicbor::encode(value, &mut *buffer)?;
+
+    Ok(())
+}
+
+/// The length of `value` encoded as per `encode`, without buffering it.
+pub(crate) fn encoded_len<T: minicbor::Encode>(
+    value: &T,
+) -> Result<usize, minicbor::encode::Error<Infallible>> {
+    let mut len = MAGIC.len() + CURRENT_SCHEMA.to_be_bytes().len();
+    minicbor::encode(value, Counter(&mut len))?;
+
+    Ok(len)
+}
+
+/// A writer which only counts the bytes written to it.
+struct Counter<'a>(&'a mut usize);
+
+impl minicbor::encode::Write for Counter<'_> {
+    type Error = Infallible;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        *self.0 += buf.len();
+        Ok(())
+    }
+}
+
+/// Split a blob into its schema version and CBOR content. A blob without the
+/// magic bytes is version 0, which cannot be mistaken for an envelope since
+/// CBOR beginning with the magic bytes would be a text string.
+fn split(bytes: &[u8]) -> Result<(u16, &[u8]), ThreadsError> {
+    match bytes.strip_prefix(MAGIC) {
+        Some([high, low, content @ ..]) => Ok((u16::from_be_bytes([*high, *low]), content)),
+        Some(_) => Err(minicbor::decode::Error::EndOfInput.into()),
+        None => Ok((0, bytes)),
+    }
+}
+
+/// Upgrade the CBOR content of a blob from one schema version to the next.
+type Migration = for<'b> fn(Cow<'b, [u8]>) -> Result<Cow<'b, [u8]>, ThreadsError>;
+
+/// A type which is stored in blobs, and which knows how to upgrade the content
+/// of blobs written by older schema versions.
+pub(crate) trait Versioned: for<'b> minicbor::Decode<'b> {
+    /// `MIGRATIONS[v]` upgrades content from version `v` to version `v + 1`.
+    const MIGRATIONS: [Migration; CURRENT_SCHEMA as usize];
+}
+
+impl Versioned for Slice {
+    const MIGRATIONS: [Migration; CURRENT_SCHEMA as usize] =
+        [|content| upgrade(content, |slice: SliceV0| Slice::from(slice))];
+}
+
+/// The cache, i.e. `Root::inner`.
+impl Versioned for MapLattice<ActorID, Slice> {
+    const MIGRATIONS: [Migration; CURRENT_SCHEMA as usize] = [|content| {
+        upgrade(content, |cache: MapLattice<ActorID, SliceV0>| {
+            cache
+                .inner
+                .into_iter()
+                .map(|(actor, slice)| (actor, Slice::from(slice)))
+                .collect::<MapLattice<_, _>>()
+        })
+    }];
+}
+
+/// The view was only ever saved within the envelope.
+impl Versioned for Detailed {
+    const MIGRATIONS: [Migration; CURRENT_SCHEMA as usize] = [unchanged];
+}
+
+/// A migration for versions which did not change the content.
+fn unchanged(content: Cow<'_, [u8]>) -> Result<Cow<'_, [u8]>, ThreadsError> {
+    Ok(content)
+}
+
+/// Decode content as `Old` and re-encode it as `upgrade` makes it.
+fn upgrade<'b, Old, New>(
+    content: Cow<'b, [u8]>,
+    upgrade: impl FnOnce(Old) -> New,
+) -> Result<Cow<'b, [u8]>, ThreadsError>
+where
+    Old: for<'c> minicbor::Decode<'c>,
+    New: minicbor::Encode,
+{
+    let mut buffer = Vec::new();
+    minicbor::encode(upgrade(minicbor::decode(&content)?), &mut buffer)?;
+
+    Ok(Cow::Owned(buffer))
+}
+
+/// A slice as written by version 0, in which messages and the versions of
+/// their content were numbered by their position, before actors had devices.
+/// Actor IDs such as `alice#0`, which then told devices apart, are kept as is.
+#[derive(minicbor::Decode)]
+struct SliceV0 {
+    #[n(0)]
+    owned: Vec<OwnedV0>,
+    #[n(1)]
+    shared: MapLattice<ActorID, MapLattice<u64, Shared>>,
+}
+
+#[derive(minicbor::Decode)]
+struct OwnedV0 {
+    #[n(0)]
+    titles: VecLattice<SetLattice<String>>,
+    #[n(1)]
+    content: Vec<Redactable<String>>,
+    #[n(2)]
+    commits: VecLattice<SetLattice<Patchset>>,
+}
+
+/// The key in version 1 of the message or version at `index` in version 0,
+/// as if written by device 0.
+fn key_v0(index: u64) -> u64 {
+    index << 16
+}
+
+impl From<SliceV0> for Slice {
+    fn from(v0: SliceV0) -> Self {
+        let owned = v0.owned.into_iter().zip(0..).map(|(owned, index)| {
+            let content = owned.content.into_iter().zip(0..);
+            let owned = Owned {
+                titles: owned.titles,
+                content: content.map(|(c, version)| (key_v0(version), c)).collect(),
+                commits: owned.commits,
+                ..Owned::default()
+            };
+            (key_v0(index), owned)
+        });
+
+        // Both the messages which are shared about, and the responses to them,
+        // are numbered like those in `owned`.
+        let shared = v0.shared.inner.into_iter().map(|(actor, messages)| {
+            let messages = messages.inner.into_iter().map(|(index, mut shared)| {
+                shared.responses = shared.responses.keys().copied().map(key_v0).collect();
+                (key_v0(index), shared)
+            });
+            (actor, messages.collect())
+        });
+
+        Slice {
+            owned: owned.collect(),
+            shared: shared.collect(),
+            display_name: None,
+        }
+    }
+}
+
+/// Decode a blob of any version up to the current schema, applying each
+/// migration from its version onwards.
+pub(crate) fn decode<T: Versioned>(bytes: &[u8]) -> Result<T, ThreadsError> {
+    let (version, content) = split(bytes)?;
+    let migrations = T::MIGRATIONS
+        .get(version as usize..)
+        .ok_or(ThreadsError::UnsupportedSchema(version))?;
+
+    let content = migrations
+        .iter()
+        .try_fold(Cow::Borrowed(content), |content, migrate| migrate(content))?;
+
+    Ok(minicbor::decode(&content)?)
+}
+
+/// Decode an actor's slice, as stored in the tree at `THREADS_REF`, upgrading
+/// it from older schema versions.
+pub fn decode_slice(bytes: &[u8]) -> Result<Slice, ThreadsError> {
+    decode(bytes)
+}
+
+#[test]
+fn slices_are_migrated() {
+    use semilog::Max;
+
+    use crate::Actor;
+
+    let mut slice = Slice::default();
+    Actor::new(&mut slice, "alice".to_owned(), 0)
+        .new_thread("Title".to_owned(), "Hello".to_owned(), [])
+        .unwrap();
+
+    let mut v1 = Vec::new();
+    encode(&slice, &mut v1).unwrap();
+    assert_eq!(decode_slice(&v1).unwrap(), slice);
+
+    // A phone's slice from before versioning, which redacted the first version
+    // of the content of its second message, and reacted to a message of bob's.
+    let v0 = [
+        0x82, 0x82, 0x83, 0x80, 0x80, 0x80, 0x83, 0x80, 0x81, 0x82, 0x02, 0x80, 0x80, 0x81, 0x82,
+        0x63, 0x62, 0x6f, 0x62, 0x81, 0x82, 0x00, 0x83, 0x80, 0x80, 0x81, 0x82, 0x6b, 0x3a, 0x68,
+        0x6f, 0x75, 0x72, 0x67, 0x6c, 0x61, 0x73, 0x73, 0x3a, 0x81, 0x01,
+    ];
+    let redacted = Owned {
+        content: MapLattice::singleton(0, Redactable::Redacted),
+        ..Owned::default()
+    };
+    let reacted = Shared {
+        reactions: MapLattice::singleton(":hourglass:".into(), Max(1)),
+        ..Shared::default()
+    };
+    assert_eq!(
+        decode_slice(&v0).unwrap(),
+        Slice {
+            owned: [(0, Owned::default()), (1 << 16, redacted)]
+                .into_iter()
+                .collect(),
+            shared: MapLattice::singleton("bob".to_owned(), MapLattice::singleton(0, reacted)),
+            display_name: None,
+        }
+    );
+
+    // Bare CBOR of the current layout is not version 0.
+    assert!(matches!(
+        decode_slice(&v1[MAGIC.len() + 2..]),
+        Err(ThreadsError::Decode(_))
+    ));
+
+    // The envelope without a version.
+    assert!(matches!(decode_slice(MAGIC), Err(ThreadsError::Decode(_))));
+}
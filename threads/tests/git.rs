@@ -0,0 +1,482 @@
+use std::{env, fs, path::PathBuf};
+
+use semilog::Semilattice;
+use threads::{
+    detailed::Detailed, Actor, CommitOptions, Root, ThreadsError, CACHE_REF, CURRENT_SCHEMA,
+    DETAILED_REF, MAGIC, THREADS_REF,
+};
+
+/// A bare repository in a fresh temporary directory.
+fn temporary_repository(name: &str) -> (PathBuf, git2::Repository) {
+    let path = env::temp_dir().join(format!("threads-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    let repo = git2::Repository::init_bare(&path).expect("Failed to create repository");
+    (path, repo)
+}
+
+fn example_root() -> Root {
+    let mut root = Root::default();
+
+    let thread = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread(
+            "Issue with feature X".to_owned(),
+            "Hello world.".to_owned(),
+            ["bug".into()],
+        )
+        .unwrap();
+    Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .reply(thread, "Can you run the tests?".to_owned())
+        .unwrap();
+
+    root
+}
+
+#[test]
+fn round_trip() {
+    let (path, repo) = temporary_repository("round-trip");
+    let root = example_root();
+
+    assert_eq!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap(),
+        Root::default()
+    );
+    assert_eq!(
+        Root::load_cache_from_git(&repo, CACHE_REF).unwrap(),
+        Root::default()
+    );
+
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "alice")
+        .unwrap();
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "bob")
+        .unwrap();
+    root.save_cache_to_git(&repo, CACHE_REF).unwrap();
+
+    assert_eq!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap(),
+        root
+    );
+    assert_eq!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, true).unwrap(),
+        root
+    );
+    assert_eq!(Root::load_cache_from_git(&repo, CACHE_REF).unwrap(), root);
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn invalid_blobs_are_errors() {
+    let (path, repo) = temporary_repository("invalid-blobs");
+
+    let blob = repo.blob(b"not CBOR").unwrap();
+    repo.reference(CACHE_REF, blob, true, "corrupt").unwrap();
+
+    assert!(matches!(
+        Root::load_cache_from_git(&repo, CACHE_REF),
+        Err(ThreadsError::Decode(_))
+    ));
+
+    let mut tree = repo.treebuilder(None).unwrap();
+    tree.insert("alice", blob, 0o160000).unwrap();
+    let tree = tree.write().unwrap();
+    repo.reference(THREADS_REF, tree, true, "corrupt").unwrap();
+
+    assert!(matches!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false),
+        Err(ThreadsError::Decode(_))
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn invalid_slices_are_errors() {
+    use threads::ValidationError;
+
+    let (path, repo) = temporary_repository("invalid-slices");
+
+    // Alice's vote on the tag, which is 1, becomes 3, which is no choice.
+    let mut json = example_root().to_json();
+    let tag = json.find("\"bug\"").unwrap();
+    let vote = tag + json[tag..].find('1').unwrap();
+    json.replace_range(vote..vote + 1, "3");
+    let root = Root::from_json(&json).unwrap();
+    root.save_all_to_git(&repo, THREADS_REF).unwrap();
+
+    assert_eq!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap(),
+        root
+    );
+    assert!(matches!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, true),
+        Err(ThreadsError::Invalid(actor, ValidationError::InvalidVote(_))) if actor == "alice"
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn independent_refs() {
+    let (path, repo) = temporary_repository("independent-refs");
+    let root = example_root();
+
+    root.save_actor_slice_to_git(&repo, "refs/topics/a", "alice")
+        .unwrap();
+    root.save_actor_slice_to_git(&repo, "refs/topics/b", "bob")
+        .unwrap();
+
+    let a = Root::coalate_slices_into_root_from_git(&repo, "refs/topics/a", false).unwrap();
+    let b = Root::coalate_slices_into_root_from_git(&repo, "refs/topics/b", false).unwrap();
+
+    assert_eq!(a.inner.keys().collect::<Vec<_>>(), ["alice"]);
+    assert_eq!(b.inner.keys().collect::<Vec<_>>(), ["bob"]);
+    assert_eq!(a.join(b), root);
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn incremental_materialization() {
+    let (path, repo) = temporary_repository("incremental");
+    let mut root = example_root();
+
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "alice")
+        .unwrap();
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "bob")
+        .unwrap();
+
+    let mut incremental = Detailed::default();
+    assert_eq!(
+        Root::materialize_incremental(&repo, THREADS_REF, &mut incremental).unwrap(),
+        2
+    );
+    assert_eq!(
+        Root::materialize_incremental(&repo, THREADS_REF, &mut incremental).unwrap(),
+        0
+    );
+
+    // Only Bob's slice changes.
+    Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .new_thread("Another issue".to_owned(), "Hello kitty.".to_owned(), [])
+        .unwrap();
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "bob")
+        .unwrap();
+
+    assert_eq!(
+        Root::materialize_incremental(&repo, THREADS_REF, &mut incremental).unwrap(),
+        1
+    );
+
+    let mut rebuilt = Detailed::default();
+    Root::materialize_incremental(&repo, THREADS_REF, &mut rebuilt).unwrap();
+    assert_eq!(rebuilt, incremental);
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn commits() {
+    let (path, repo) = temporary_repository("commits");
+    let root = example_root();
+
+    let signature = git2::Signature::now("Alice", "alice@example.com").unwrap();
+    let sign = |content: &str| Ok(format!("signature of {} bytes", content.len()));
+
+    let first = root
+        .commit_actor_slice_to_git(
+            &repo,
+            THREADS_REF,
+            "alice",
+            &CommitOptions {
+                author: signature.clone(),
+                committer: signature.clone(),
+                message: "Update alice",
+                sign: None,
+            },
+        )
+        .unwrap();
+    let second = root
+        .commit_actor_slice_to_git(
+            &repo,
+            THREADS_REF,
+            "bob",
+            &CommitOptions {
+                author: signature.clone(),
+                committer: signature,
+                message: "Update bob",
+                sign: Some(&sign),
+            },
+        )
+        .unwrap();
+
+    let commit = repo
+        .find_reference(THREADS_REF)
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    assert_eq!(commit.id(), second);
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [first]);
+    assert_eq!(commit.author().name(), Some("Alice"));
+
+    let (signature, _) = repo.extract_signature(&second, None).unwrap();
+    assert!(signature.as_str().unwrap().starts_with("signature of"));
+    assert!(repo.extract_signature(&first, None).is_err());
+
+    assert_eq!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap(),
+        root
+    );
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn save_all() {
+    let (path, repo) = temporary_repository("save-all");
+    let root = example_root();
+
+    root.save_all_to_git(&repo, THREADS_REF).unwrap();
+
+    assert_eq!(
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap(),
+        root
+    );
+    assert_eq!(
+        repo.find_reference(THREADS_REF)
+            .unwrap()
+            .peel_to_tree()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn streaming() {
+    let (path, repo) = temporary_repository("streaming");
+    let root = example_root();
+
+    root.save_all_to_git(&repo, THREADS_REF).unwrap();
+
+    let mut streamed = Root::default();
+    let mut detailed = Detailed::default();
+    Root::stream_slices_from_git(&repo, THREADS_REF, |actor, slice| {
+        streamed.inner.insert(actor.clone(), slice.clone());
+        detailed = std::mem::take(&mut detailed).join_root(Root {
+            inner: [(actor, slice)].into_iter().collect(),
+        });
+    })
+    .unwrap();
+
+    assert_eq!(
+        streamed,
+        Root::coalate_slices_into_root_from_git(&repo, THREADS_REF, false).unwrap()
+    );
+    assert_eq!(detailed, Detailed::default().join_root(root));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn unchanged_cache() {
+    let (path, repo) = temporary_repository("unchanged-cache");
+    let mut root = example_root();
+
+    // Bare repositories only log reference updates when asked to.
+    repo.config()
+        .unwrap()
+        .set_str("core.logAllRefUpdates", "always")
+        .unwrap();
+
+    assert!(root.save_cache_if_changed(&repo, CACHE_REF).unwrap());
+    let reflog = || repo.reflog(CACHE_REF).unwrap().len();
+    assert_eq!(reflog(), 1);
+
+    assert!(!root.save_cache_if_changed(&repo, CACHE_REF).unwrap());
+    assert_eq!(reflog(), 1);
+
+    Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0)
+        .new_thread("Another".to_owned(), "Hello again.".to_owned(), [])
+        .unwrap();
+    assert!(root.save_cache_if_changed(&repo, CACHE_REF).unwrap());
+    assert_eq!(reflog(), 2);
+    assert_eq!(Root::load_cache_from_git(&repo, CACHE_REF).unwrap(), root);
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn unknown_actors() {
+    let (path, repo) = temporary_repository("unknown-actors");
+    let root = example_root();
+
+    assert!(matches!(
+        root.save_actor_slice_to_git(&repo, THREADS_REF, "carol"),
+        Err(ThreadsError::UnknownActor(actor)) if actor == "carol"
+    ));
+    assert!(repo.find_reference(THREADS_REF).is_err());
+
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "alice")
+        .unwrap();
+    let tree = || {
+        repo.find_reference(THREADS_REF)
+            .unwrap()
+            .peel_to_tree()
+            .unwrap()
+    };
+    assert!(root
+        .save_actor_slice_to_git(&repo, THREADS_REF, "carol")
+        .is_err());
+    assert_eq!(tree().len(), 1);
+    assert!(tree().get_name("carol").is_none());
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn detailed_round_trip() {
+    let (path, repo) = temporary_repository("detailed-round-trip");
+    let root = example_root();
+
+    assert_eq!(
+        Detailed::load_from_git(&repo, DETAILED_REF).unwrap(),
+        Detailed::default()
+    );
+
+    root.save_all_to_git(&repo, THREADS_REF).unwrap();
+    let mut detailed = Detailed::default();
+    Root::materialize_incremental(&repo, THREADS_REF, &mut detailed).unwrap();
+    detailed.save_to_git(&repo, DETAILED_REF).unwrap();
+
+    let mut loaded = Detailed::load_from_git(&repo, DETAILED_REF).unwrap();
+    assert_eq!(loaded, detailed);
+
+    // Which slices were materialized is also restored.
+    assert_eq!(
+        Root::materialize_incremental(&repo, THREADS_REF, &mut loaded).unwrap(),
+        0
+    );
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+/// The cache of the test vector in `example-thread.rs`, as encoded before the
+/// schema was versioned.
+const CACHE_V0: [u8; 250] = [
+    0x82, 0x82, 0x65, 0x61, 0x6c, 0x69, 0x63, 0x65, 0x82, 0x82, 0x83, 0x81, 0x81, 0x82, 0x74, 0x49,
+    0x73, 0x73, 0x75, 0x65, 0x20, 0x77, 0x69, 0x74, 0x68, 0x20, 0x66, 0x65, 0x61, 0x74, 0x75, 0x72,
+    0x65, 0x20, 0x58, 0x80, 0x81, 0x82, 0x01, 0x81, 0x78, 0x23, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x20,
+    0x77, 0x6f, 0x72, 0x6c, 0x64, 0x2e, 0x20, 0x49, 0x20, 0x68, 0x61, 0x76, 0x65, 0x20, 0x74, 0x68,
+    0x69, 0x73, 0x20, 0x69, 0x73, 0x73, 0x75, 0x65, 0x20, 0x5b, 0x2e, 0x2e, 0x5d, 0x80, 0x83, 0x80,
+    0x81, 0x82, 0x02, 0x80, 0x80, 0x82, 0x82, 0x67, 0x61, 0x6c, 0x69, 0x63, 0x65, 0x23, 0x30, 0x81,
+    0x82, 0x00, 0x83, 0x80, 0x82, 0x82, 0x63, 0x62, 0x75, 0x67, 0x81, 0x01, 0x82, 0x6d, 0x69, 0x6e,
+    0x63, 0x6f, 0x72, 0x72, 0x65, 0x63, 0x74, 0x2d, 0x74, 0x61, 0x67, 0x81, 0x01, 0x80, 0x82, 0x63,
+    0x62, 0x6f, 0x62, 0x81, 0x82, 0x00, 0x83, 0x81, 0x82, 0x01, 0x80, 0x80, 0x81, 0x82, 0x6b, 0x3a,
+    0x68, 0x6f, 0x75, 0x72, 0x67, 0x6c, 0x61, 0x73, 0x73, 0x3a, 0x81, 0x01, 0x82, 0x63, 0x62, 0x6f,
+    0x62, 0x82, 0x81, 0x83, 0x80, 0x81, 0x82, 0x01, 0x81, 0x78, 0x1b, 0x48, 0x75, 0x68, 0x2e, 0x20,
+    0x43, 0x61, 0x6e, 0x20, 0x79, 0x6f, 0x75, 0x20, 0x72, 0x75, 0x6e, 0x20, 0x74, 0x68, 0x65, 0x20,
+    0x74, 0x65, 0x73, 0x74, 0x73, 0x3f, 0x80, 0x81, 0x82, 0x67, 0x61, 0x6c, 0x69, 0x63, 0x65, 0x23,
+    0x30, 0x81, 0x82, 0x00, 0x83, 0x81, 0x82, 0x00, 0x80, 0x82, 0x82, 0x6d, 0x69, 0x6e, 0x63, 0x6f,
+    0x72, 0x72, 0x65, 0x63, 0x74, 0x2d, 0x74, 0x61, 0x67, 0x81, 0x02, 0x82, 0x6a, 0x72, 0x65, 0x67,
+    0x72, 0x65, 0x73, 0x73, 0x69, 0x6f, 0x6e, 0x81, 0x01, 0x80,
+];
+
+#[test]
+fn schema_versions() {
+    let (path, repo) = temporary_repository("schema-versions");
+    let root = example_root();
+
+    root.save_cache_to_git(&repo, CACHE_REF).unwrap();
+    let blob = repo
+        .find_reference(CACHE_REF)
+        .unwrap()
+        .peel_to_blob()
+        .unwrap();
+    assert!(blob.content().starts_with(MAGIC));
+    assert_eq!(blob.content()[4..6], CURRENT_SCHEMA.to_be_bytes());
+
+    // Blobs written before the envelope was introduced are migrated.
+    let blob = repo.blob(&CACHE_V0).unwrap();
+    repo.reference(CACHE_REF, blob, true, "v0").unwrap();
+    let migrated = Root::load_cache_from_git(&repo, CACHE_REF).unwrap();
+    assert_eq!(migrated.actors().collect::<Vec<_>>(), ["alice", "bob"]);
+    assert_eq!(
+        (migrated.stats().threads, migrated.stats().messages),
+        (1, 3)
+    );
+
+    // Bare CBOR of the current layout is not version 0.
+    let mut buffer = Vec::new();
+    minicbor::encode(&root.inner, &mut buffer).unwrap();
+    let blob = repo.blob(&buffer).unwrap();
+    repo.reference(CACHE_REF, blob, true, "bare").unwrap();
+    assert!(matches!(
+        Root::load_cache_from_git(&repo, CACHE_REF),
+        Err(ThreadsError::Decode(_))
+    ));
+
+    // Blobs from a future version are rejected.
+    let mut buffer = MAGIC.to_vec();
+    buffer.extend_from_slice(&(CURRENT_SCHEMA + 1).to_be_bytes());
+    minicbor::encode(&root.inner, &mut buffer).unwrap();
+    let blob = repo.blob(&buffer).unwrap();
+    repo.reference(CACHE_REF, blob, true, "future").unwrap();
+    assert!(matches!(
+        Root::load_cache_from_git(&repo, CACHE_REF),
+        Err(ThreadsError::UnsupportedSchema(version)) if version == CURRENT_SCHEMA + 1
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn unchanged_slices() {
+    let (path, repo) = temporary_repository("unchanged-slices");
+    let mut root = example_root();
+    root.save_actor_slice_to_git(&repo, THREADS_REF, "alice")
+        .unwrap();
+
+    let mut seen = None;
+    let mut coalated = Root::default();
+    assert!(Root::coalate_slices_into_root_from_git_if_changed(
+        &repo,
+        THREADS_REF,
+        true,
+        &mut seen,
+        &mut coalated
+    )
+    .unwrap());
+    assert!(seen.is_some());
+    assert_eq!(coalated.actors().collect::<Vec<_>>(), ["alice"]);
+
+    // An unchanged tree is not read again, so the stale root is kept.
+    let mut stale = Root::default();
+    assert!(!Root::coalate_slices_into_root_from_git_if_changed(
+        &repo,
+        THREADS_REF,
+        true,
+        &mut seen,
+        &mut stale
+    )
+    .unwrap());
+    assert_eq!(stale, Root::default());
+
+    Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0)
+        .new_thread("Another".to_owned(), "Hi".to_owned(), [])
+        .unwrap();
+    root.save_all_to_git(&repo, THREADS_REF).unwrap();
+    assert!(Root::coalate_slices_into_root_from_git_if_changed(
+        &repo,
+        THREADS_REF,
+        true,
+        &mut seen,
+        &mut coalated
+    )
+    .unwrap());
+    assert_eq!(coalated, root);
+
+    fs::remove_dir_all(path).unwrap();
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "serde")]
+
+use threads::{Actor, Root};
+
+fn example_root() -> Root {
+    let mut root = Root::default();
+    let mut alice = Actor::new(root.inner.entry_mut("alice"), "alice".to_owned(), 0);
+    let id = alice
+        .new_thread("Title".to_owned(), "Hello".to_owned(), ["bug".into()])
+        .unwrap();
+    let version = alice.edit(id.1, "Hello!".to_owned()).unwrap();
+    alice.redact(id.clone(), version).unwrap();
+
+    let mut bob = Actor::new(root.inner.entry_mut("bob"), "bob".to_owned(), 0);
+    bob.react(id.clone(), ":+1:".into(), true);
+    bob.reply(id, "Hi".to_owned()).unwrap();
+
+    root
+}
+
+#[test]
+fn round_trip() {
+    let root = example_root();
+
+    assert_eq!(Root::from_json(&root.to_json()).unwrap(), root);
+    assert!(Root::from_json("{").is_err());
+}
+
+#[test]
+fn golden() {
+    assert_eq!(
+        example_root().to_json(),
+        include_str!("example-thread.json")
+    );
+}